@@ -7,11 +7,35 @@ impl Plugin for BackgroundPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(BackgroundSpeed(BACKGROUND_BASE_SPEED))
             .add_startup_system(spawn_background_system)
-            .add_system(bg_movement_system)
-            .add_system(handle_wave_complete_event);
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_background_speed_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Title)
+                    .with_system(bg_movement_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(bg_movement_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new().with_system(handle_wave_complete_event),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(bg_movement_system),
+            );
     }
 }
 
+fn reset_background_speed_system(
+    mut background_speed: ResMut<BackgroundSpeed>,
+) {
+    background_speed.0 = BACKGROUND_BASE_SPEED;
+}
+
 fn spawn_background_system(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct BombPlugin;
+
+impl Plugin for BombPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_systems(
+            SimulationStep::Input,
+            SystemSet::new().with_system(player_bomb_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(latch_bomb_button_system)
+                .with_system(bomb_flash_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Playing)
+                .with_system(despawn_with::<BombFlash>),
+        );
+    }
+}
+
+fn bomb_button(
+    keyboard: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+) -> bool {
+    keyboard.just_pressed(KeyCode::B)
+        || gamepads.iter().any(|gamepad| {
+            buttons.just_pressed(GamepadButton::new(
+                gamepad,
+                GamepadButtonType::North,
+            ))
+        })
+}
+
+fn latch_bomb_button_system(
+    mut input_latch: ResMut<InputLatch>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    input_latch.bomb |= bomb_button(&keyboard, &gamepads, &buttons);
+}
+
+/// Spends a bomb: every enemy laser is gone, every enemy takes a beating and
+/// the player gets a moment to breathe.
+fn player_bomb_system(
+    mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+    mut input_latch: ResMut<InputLatch>,
+    mut batch_explosions_event: EventWriter<BatchExplosionsEvent>,
+    mut enemy_take_hit_event: EventWriter<EnemyTakeHitEvent>,
+    query_player: Query<(Entity, Option<&Invulnerable>), With<Player>>,
+    query_lasers: Query<(Entity, &Transform), (With<Laser>, With<FromEnemy>)>,
+    query_enemies: Query<(Entity, &SpawnIndex, &Transform), With<Enemy>>,
+) {
+    if !std::mem::take(&mut input_latch.bomb)
+        || !player_state.is_alive
+        || player_state.bombs <= 0
+    {
+        return;
+    }
+    let (player, invulnerable) = match query_player.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    player_state.bombs -= 1;
+
+    // gone within the tick, they can't hit the player on the way out
+    let mut positions: Vec<Vec3> = Vec::new();
+    for (laser_entity, laser_tf) in query_lasers.iter() {
+        commands.entity(laser_entity).despawn_recursive();
+        positions.push(laser_tf.translation);
+    }
+    // hits roll drops, so they go out in spawn order
+    let mut enemies: Vec<_> = query_enemies.iter().collect();
+    enemies.sort_by_key(|(_, index, _)| **index);
+    for (enemy_entity, _, enemy_tf) in enemies {
+        positions.push(enemy_tf.translation);
+        enemy_take_hit_event.send(EnemyTakeHitEvent {
+            entity: enemy_entity,
+            position: enemy_tf.translation,
+            damage: BOMB_DAMAGE,
+        });
+    }
+    batch_explosions_event.send(BatchExplosionsEvent {
+        positions,
+        with_sound: true,
+    });
+
+    Invulnerable::grant(
+        &mut commands,
+        player,
+        invulnerable,
+        BOMB_INVULNERABILITY,
+    );
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..default()
+            },
+            background_color: Color::rgba(1., 1., 1., BOMB_FLASH_ALPHA).into(),
+            ..default()
+        },
+        BombFlash(Timer::from_seconds(BOMB_FLASH_TIME, TimerMode::Once)),
+    ));
+}
+
+fn bomb_flash_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut BombFlash, &mut BackgroundColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, mut color) in query.iter_mut() {
+        flash.0.tick(time.delta());
+        color.0.set_a(BOMB_FLASH_ALPHA * flash.0.percent_left());
+        if flash.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::f32::consts::TAU;
+
+use crate::patterns::sine_weave_system;
+use crate::prelude::*;
+use bevy::prelude::*;
+use rand::Rng;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_systems(
+            SimulationStep::Steering,
+            // a new phase changes how the boss sways
+            SystemSet::new()
+                .with_system(boss_phase_system.before(sine_weave_system)),
+        )
+        .add_simulation_systems(
+            SimulationStep::Cleanup,
+            SystemSet::new().with_system(boss_death_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(spawn_boss_health_bar_system)
+                .with_system(update_boss_health_bar_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Playing)
+                .with_system(despawn_with::<Boss>)
+                .with_system(despawn_with::<BossHealthBar>),
+        );
+    }
+}
+
+/// An attack phase of a boss, active once its health drops to
+/// `health_fraction` of the maximum. Bosses open with the weapon of their
+/// `EnemyDefinition`.
+struct BossPhase {
+    health_fraction: f32,
+    pattern: FirePattern,
+    fire_interval: f32,
+    bullet_speed: f32,
+    sway_frequency: f32,
+}
+
+static BOSS_PHASES: [BossPhase; 2] = [
+    BossPhase {
+        health_fraction: 0.66,
+        pattern: FirePattern::Spiral {
+            count: 6,
+            step: 0.25,
+        },
+        fire_interval: 0.35,
+        bullet_speed: 0.55,
+        sway_frequency: 0.25,
+    },
+    BossPhase {
+        health_fraction: 0.33,
+        pattern: FirePattern::Burst {
+            shots: 5,
+            interval: 0.1,
+        },
+        fire_interval: 1.,
+        bullet_speed: 1.1,
+        sway_frequency: 0.4,
+    },
+];
+
+/// Spawns the boss above the screen, it swoops in and sways from side to
+/// side while it fights. Callers are responsible for keeping `EnemyCount`
+/// in sync.
+pub fn spawn_boss(
+    commands: &mut Commands,
+    spawn_counter: &mut SpawnCounter,
+    game_textures: &GameTextures,
+    window_size: &WindowSize,
+    velocity: Vec2,
+) -> Entity {
+    let kind = EnemyKind::Boss;
+    let definition = kind.definition();
+    let size = definition.hitbox();
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: game_textures.enemies[&kind].clone(),
+                sprite: Sprite {
+                    color: definition.color,
+                    flip_y: true,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_xyz(
+                    0.,
+                    window_size.height / 2. + size.y,
+                    1.,
+                ),
+                ..default()
+            },
+            Enemy,
+            kind,
+            Boss::default(),
+            spawn_counter.next(),
+            Health(definition.health),
+            Collision(size),
+            // wings across the top, the hull below them
+            Collider(Vec::new())
+                .with(
+                    Vec2::new(0., size.y * 0.2),
+                    Collider::fit(Vec2::new(size.x, size.y * 0.4)),
+                )
+                .with(
+                    Vec2::new(0., -size.y * 0.15),
+                    Collider::circle(size.x * 0.22),
+                ),
+            CollisionLayers::enemy(),
+            Movable { auto_despawn: true },
+            Velocity(velocity),
+            EnemyWeapon::new(
+                definition.fire_pattern,
+                definition.fire_interval,
+                definition.bullet_speed,
+            ),
+            SwoopHover {
+                hover_y: window_size.height / 4.,
+                hover_time: f32::INFINITY,
+                speed: velocity.length(),
+                phase: SwoopPhase::Swoop,
+                elapsed: 0.,
+            },
+            SineWeave {
+                amplitude: BOSS_SWAY_AMPLITUDE,
+                frequency: BOSS_SWAY_FREQUENCY,
+                elapsed: 0.,
+            },
+        ))
+        .with_children(|parent| {
+            // the weak points sit on the engines
+            for (x, y) in BOSS_WEAK_POINTS {
+                parent.spawn((
+                    SpriteBundle {
+                        texture: game_textures.enemy_lasers[&kind].clone(),
+                        sprite: Sprite {
+                            color: Color::rgb(1., 0.3, 0.3),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(x, y, 1.),
+                        ..default()
+                    },
+                    WeakPoint {
+                        multiplier: BOSS_WEAK_POINT_MULTIPLIER,
+                    },
+                    spawn_counter.next(),
+                    Collision::from(SIZE_BOSS_WEAK_POINT),
+                    Collider::circle(SIZE_BOSS_WEAK_POINT.0 / 2.),
+                    // only lasers care about weak points, ramming hits the
+                    // boss itself
+                    CollisionLayers::new(
+                        CollisionLayers::ENEMY,
+                        CollisionLayers::PLAYER_LASER,
+                    ),
+                ));
+            }
+        })
+        .id()
+}
+
+fn boss_phase_system(
+    mut query: Query<
+        (
+            &Health,
+            &EnemyKind,
+            &mut Boss,
+            &mut EnemyWeapon,
+            &mut SineWeave,
+        ),
+        With<Enemy>,
+    >,
+) {
+    for (health, kind, mut boss, mut weapon, mut weave) in query.iter_mut() {
+        let fraction = health.0 as f32 / kind.definition().health as f32;
+        let phase = BOSS_PHASES
+            .iter()
+            .filter(|phase| fraction <= phase.health_fraction)
+            .count();
+        if phase <= boss.phase {
+            continue;
+        }
+
+        boss.phase = phase;
+        let phase = &BOSS_PHASES[phase - 1];
+        *weapon = EnemyWeapon::new(
+            phase.pattern,
+            phase.fire_interval,
+            phase.bullet_speed,
+        );
+        weave.frequency = phase.sway_frequency;
+    }
+}
+
+fn spawn_boss_health_bar_system(
+    mut commands: Commands,
+    query: Query<(), Added<Boss>>,
+) {
+    if query.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(10.),
+                        left: Val::Percent(20.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Percent(60.), Val::Px(12.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.2, 0.2, 0.2, 0.8).into(),
+                ..default()
+            },
+            BossHealthBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(
+                            Val::Percent(100.),
+                            Val::Percent(100.),
+                        ),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.9, 0.2, 0.2).into(),
+                    ..default()
+                },
+                BossHealthBarFill,
+            ));
+        });
+}
+
+fn update_boss_health_bar_system(
+    mut commands: Commands,
+    query_boss: Query<(&Health, &EnemyKind), (With<Boss>, With<Enemy>)>,
+    mut query_fill: Query<&mut Style, With<BossHealthBarFill>>,
+    query_bar: Query<Entity, With<BossHealthBar>>,
+) {
+    match query_boss.get_single() {
+        Ok((health, kind)) => {
+            let fraction = health.0 as f32 / kind.definition().health as f32;
+            for mut style in query_fill.iter_mut() {
+                style.size.width = Val::Percent(fraction.max(0.) * 100.);
+            }
+        }
+        Err(_) => {
+            for entity in query_bar.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Waves of explosions all over the boss, the last one takes it with it.
+fn boss_death_system(
+    mut commands: Commands,
+    mut batch_explosions_event: EventWriter<BatchExplosionsEvent>,
+    mut query: Query<(
+        Entity,
+        &Transform,
+        &Collision,
+        &mut Velocity,
+        &mut BossDeath,
+    )>,
+    mut rng: ResMut<GameRng>,
+    time: Res<SimulationTime>,
+) {
+    for (entity, tf, collision, mut velocity, mut death) in query.iter_mut() {
+        velocity.0 = Vec2::ZERO;
+        if !death.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let center = tf.translation;
+        let half = collision.0 / 2.;
+        let mut positions: Vec<Vec3> = (0..BOSS_DEATH_BLASTS)
+            .map(|_| {
+                center
+                    + Vec3::new(
+                        rng.gen_range(-half.x..half.x),
+                        rng.gen_range(-half.y..half.y),
+                        0.,
+                    )
+            })
+            .collect();
+
+        death.waves_left -= 1;
+        if death.waves_left == 0 {
+            // one last ring of explosions for good measure
+            positions.extend((0..8).map(|i| {
+                let angle = TAU * i as f32 / 8.;
+                center + Vec3::new(angle.cos(), angle.sin(), 0.) * half.x
+            }));
+            // gone within the tick, not whenever the explosions are drawn
+            commands.entity(entity).despawn_recursive();
+        }
+        batch_explosions_event.send(BatchExplosionsEvent {
+            positions,
+            with_sound: true,
+        });
+    }
+}
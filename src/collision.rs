@@ -0,0 +1,591 @@
+use std::collections::BTreeSet;
+
+use crate::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialHash::new(COLLISION_CELL_SIZE))
+            .add_simulation_systems(
+                SimulationStep::Collision,
+                SystemSet::new().with_system(detect_collisions_system),
+            )
+            .add_simulation_event::<CollisionEvent>();
+    }
+}
+
+/// A single shape of a `Collider`, in world pixels.
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    Circle {
+        radius: f32,
+    },
+    /// Circle swept along a segment, `half_segment` goes from the center to
+    /// one end of it.
+    Capsule {
+        half_segment: Vec2,
+        radius: f32,
+    },
+}
+
+impl Shape {
+    /// Everything is a capsule, a circle just has no length.
+    fn segment(self) -> (Vec2, f32) {
+        match self {
+            Shape::Circle { radius } => (Vec2::ZERO, radius),
+            Shape::Capsule {
+                half_segment,
+                radius,
+            } => (half_segment, radius),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColliderPart {
+    /// from the entity's translation, before rotation
+    pub offset: Vec2,
+    pub shape: Shape,
+}
+
+/// What actually gets hit, made of one or more shapes. Follows the
+/// translation and rotation of its entity but not its scale, sprites are
+/// scaled and flipped all over the place.
+#[derive(Component, Debug, Clone)]
+pub struct Collider(pub Vec<ColliderPart>);
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Self(vec![ColliderPart {
+            offset: Vec2::ZERO,
+            shape: Shape::Circle { radius },
+        }])
+    }
+
+    pub fn capsule(half_segment: Vec2, radius: f32) -> Self {
+        Self(vec![ColliderPart {
+            offset: Vec2::ZERO,
+            shape: Shape::Capsule {
+                half_segment,
+                radius,
+            },
+        }])
+    }
+
+    /// The largest capsule fitting inside a `size` box, lying along its
+    /// longer side. Good enough for most sprites.
+    pub fn fit(size: Vec2) -> Self {
+        let radius = size.min_element() / 2.;
+        let half_segment = if size.x > size.y {
+            Vec2::new(size.x / 2. - radius, 0.)
+        } else {
+            Vec2::new(0., size.y / 2. - radius)
+        };
+        Self::capsule(half_segment, radius)
+    }
+
+    /// Adds another shape at `offset`, for compound colliders.
+    pub fn with(mut self, offset: Vec2, collider: Collider) -> Self {
+        self.0
+            .extend(collider.0.into_iter().map(|part| ColliderPart {
+                offset: part.offset + offset,
+                ..part
+            }));
+        self
+    }
+
+    pub fn intersects(
+        &self,
+        tf: &Transform,
+        other: &Collider,
+        other_tf: &Transform,
+    ) -> bool {
+        self.intersects_swept(tf, Vec2::ZERO, other, other_tf)
+    }
+
+    /// Like `intersects`, but anywhere along the last `travel` it moved to
+    /// get to `tf`. Exact for circles and for capsules moving along their
+    /// length, which is how projectiles fly.
+    pub fn intersects_swept(
+        &self,
+        tf: &Transform,
+        travel: Vec2,
+        other: &Collider,
+        other_tf: &Transform,
+    ) -> bool {
+        self.0.iter().any(|part| {
+            let (a, a_half, a_radius) = place(part, tf);
+            // the part where it is, where it was and its center in between
+            let path = [
+                (a, a_half),
+                (a - travel, a_half),
+                (a - travel / 2., travel / 2.),
+            ];
+            let path = if travel == Vec2::ZERO {
+                &path[..1]
+            } else {
+                &path[..]
+            };
+            other.0.iter().any(|other_part| {
+                let (b, b_half, b_radius) = place(other_part, other_tf);
+                let reach = a_radius + b_radius;
+                path.iter().any(|(a, a_half)| {
+                    segment_distance_squared(
+                        *a - *a_half,
+                        *a + *a_half,
+                        b - b_half,
+                        b + b_half,
+                    ) <= reach * reach
+                })
+            })
+        })
+    }
+}
+
+/// Fast movers that could skip over a collider between two frames, they
+/// are tested along the whole way from where they were last frame.
+#[derive(Component, Debug, Default)]
+pub struct Swept {
+    previous: Option<Vec2>,
+}
+
+/// Which layers an entity is on and which layers it collides with. Two
+/// entities only touch when each is on a layer the other one looks for.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub mask: u32,
+}
+
+impl CollisionLayers {
+    pub const PLAYER: u32 = 1 << 0;
+    pub const ENEMY: u32 = 1 << 1;
+    pub const PLAYER_LASER: u32 = 1 << 2;
+    pub const ENEMY_LASER: u32 = 1 << 3;
+    pub const PICKUP: u32 = 1 << 4;
+
+    pub const fn new(memberships: u32, mask: u32) -> Self {
+        Self { memberships, mask }
+    }
+
+    pub const fn player() -> Self {
+        Self::new(Self::PLAYER, Self::ENEMY | Self::ENEMY_LASER | Self::PICKUP)
+    }
+
+    pub const fn enemy() -> Self {
+        Self::new(Self::ENEMY, Self::PLAYER | Self::PLAYER_LASER)
+    }
+
+    pub const fn player_laser() -> Self {
+        Self::new(Self::PLAYER_LASER, Self::ENEMY)
+    }
+
+    pub const fn enemy_laser() -> Self {
+        Self::new(Self::ENEMY_LASER, Self::PLAYER)
+    }
+
+    pub const fn pickup() -> Self {
+        Self::new(Self::PICKUP, Self::PLAYER)
+    }
+
+    pub fn interacts(self, other: Self) -> bool {
+        self.memberships & other.mask != 0 && other.memberships & self.mask != 0
+    }
+}
+
+impl Collider {
+    /// Smallest box around every part, as `(min, max)` corners.
+    fn bounds(&self, tf: &Transform) -> (Vec2, Vec2) {
+        self.0.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), part| {
+                let (center, half_segment, radius) = place(part, tf);
+                let extent = half_segment.abs() + Vec2::splat(radius);
+                (min.min(center - extent), max.max(center + extent))
+            },
+        )
+    }
+}
+
+/// Uniform grid of every `Collider`, rebuilt each frame. An entity sits in
+/// every cell its bounds touch, so only neighbours get tested against each
+/// other.
+#[derive(Resource)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<SpawnIndex>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, index: SpawnIndex, min: Vec2, max: Vec2) {
+        let min = (min / self.cell_size).floor().as_ivec2();
+        let max = (max / self.cell_size).floor().as_ivec2();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Every pair of entities sharing a cell, once and in spawn order.
+    pub fn pairs(&self) -> BTreeSet<(SpawnIndex, SpawnIndex)> {
+        let mut pairs = BTreeSet::new();
+        for entities in self.cells.values() {
+            for (i, a) in entities.iter().enumerate() {
+                for b in entities[i + 1..].iter() {
+                    pairs.insert((*a.min(b), *a.max(b)));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Broad phase through the `SpatialHash`, then exact shapes only for pairs
+/// whose layers interact.
+fn detect_collisions_system(
+    mut spatial_hash: ResMut<SpatialHash>,
+    mut collision_event: EventWriter<CollisionEvent>,
+    mut query: Query<(
+        Entity,
+        &SpawnIndex,
+        &Transform,
+        &Collider,
+        &CollisionLayers,
+        Option<&Parent>,
+        Option<&mut Swept>,
+    )>,
+    query_parents: Query<&Transform>,
+) {
+    spatial_hash.clear();
+    let mut placed = HashMap::new();
+    for (entity, index, tf, collider, layers, parent, swept) in query.iter_mut()
+    {
+        // children like boss weak points are placed relative to their
+        // parent, `GlobalTransform` is only up to date for drawing
+        let parent_tf =
+            parent.and_then(|parent| query_parents.get(parent.get()).ok());
+        let tf = match parent_tf {
+            Some(parent_tf) => parent_tf.mul_transform(*tf),
+            None => *tf,
+        };
+        let position = tf.translation.truncate();
+        let travel = match swept {
+            Some(mut swept) => {
+                let previous = swept.previous.replace(position);
+                previous.map_or(Vec2::ZERO, |previous| position - previous)
+            }
+            None => Vec2::ZERO,
+        };
+
+        let (min, max) = collider.bounds(&tf);
+        let (min, max) = (min.min(min - travel), max.max(max - travel));
+        spatial_hash.insert(*index, min, max);
+        placed.insert(*index, (entity, tf, travel, collider, *layers));
+    }
+
+    for (a, b) in spatial_hash.pairs() {
+        let (a, a_tf, a_travel, a_collider, a_layers) = placed[&a];
+        let (b, b_tf, b_travel, b_collider, b_layers) = placed[&b];
+        // sweeping `a` by how it moved relative to `b` covers both moving
+        if a_layers.interacts(b_layers)
+            && a_collider.intersects_swept(
+                &a_tf,
+                a_travel - b_travel,
+                b_collider,
+                &b_tf,
+            )
+        {
+            collision_event.send(CollisionEvent { a, b });
+        }
+    }
+}
+
+impl CollisionEvent {
+    /// `(matching, other)` if either entity passes `matches`, for systems
+    /// that only care about one side of the contact.
+    pub fn either(
+        &self,
+        matches: impl Fn(Entity) -> bool,
+    ) -> Option<(Entity, Entity)> {
+        if matches(self.a) {
+            Some((self.a, self.b))
+        } else if matches(self.b) {
+            Some((self.b, self.a))
+        } else {
+            None
+        }
+    }
+}
+
+/// Center, half segment and radius of a part in world space.
+fn place(part: &ColliderPart, tf: &Transform) -> (Vec2, Vec2, f32) {
+    let (half_segment, radius) = part.shape.segment();
+    let offset = tf.rotation * part.offset.extend(0.);
+    let center = tf.translation.truncate() + offset.truncate();
+    let half_segment = (tf.rotation * half_segment.extend(0.)).truncate();
+    (center, half_segment, radius)
+}
+
+/// Squared distance between the closest points of segments `p1 q1` and
+/// `p2 q2`, from Ericson's Real-Time Collision Detection.
+fn segment_distance_squared(p1: Vec2, q1: Vec2, p2: Vec2, q2: Vec2) -> f32 {
+    let (d1, d2, r) = (q1 - p1, q2 - p2, p1 - p2);
+    let (a, e, f) = (d1.length_squared(), d2.length_squared(), d2.dot(r));
+
+    let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+        (0., 0.)
+    } else if a <= f32::EPSILON {
+        (0., (f / e).clamp(0., 1.))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0., 1.), 0.)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            // parallel segments, any point will do
+            let s = if denom > 0. {
+                ((b * f - c * e) / denom).clamp(0., 1.)
+            } else {
+                0.
+            };
+            let t = (b * s + f) / e;
+            if t < 0. {
+                ((-c / a).clamp(0., 1.), 0.)
+            } else if t > 1. {
+                (((b - c) / a).clamp(0., 1.), 1.)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    ((p1 + d1 * s) - (p2 + d2 * t)).length_squared()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn segment_distances() {
+        let d = |p1: (f32, f32), q1: (f32, f32), p2: (f32, f32), q2| {
+            let v = |(x, y): (f32, f32)| Vec2::new(x, y);
+            segment_distance_squared(v(p1), v(q1), v(p2), v(q2))
+        };
+        // crossing
+        assert_eq!(d((-1., 0.), (1., 0.), (0., -1.), (0., 1.)), 0.);
+        // parallel, side by side
+        assert_eq!(d((0., 0.), (4., 0.), (1., 3.), (3., 3.)), 9.);
+        // in line, closest at the ends
+        assert_eq!(d((0., 0.), (1., 0.), (3., 0.), (5., 0.)), 4.);
+        // points and a point against a segment
+        assert_eq!(d((0., 0.), (0., 0.), (3., 4.), (3., 4.)), 25.);
+        assert_eq!(d((2., 2.), (2., 2.), (0., 0.), (4., 0.)), 4.);
+        assert_eq!(d((0., 0.), (4., 0.), (6., 2.), (6., 2.)), 8.);
+    }
+
+    fn touches(
+        a: &Collider,
+        a_tf: Transform,
+        b: &Collider,
+        b_tf: Transform,
+    ) -> bool {
+        // and it has to be the same from either side
+        let touches = a.intersects(&a_tf, b, &b_tf);
+        assert_eq!(touches, b.intersects(&b_tf, a, &a_tf));
+        touches
+    }
+
+    #[test]
+    fn circles_touch_within_both_radii() {
+        let (a, b) = (Collider::circle(5.), Collider::circle(3.));
+        assert!(touches(&a, at(0., 0.), &b, at(8., 0.)));
+        assert!(touches(&a, at(0., 0.), &b, at(0., 7.)));
+        assert!(!touches(&a, at(0., 0.), &b, at(8.1, 0.)));
+    }
+
+    #[test]
+    fn capsules_reach_along_their_length() {
+        let capsule = Collider::capsule(Vec2::new(10., 0.), 2.);
+        let circle = Collider::circle(3.);
+        // past the end of the segment by both radii
+        assert!(touches(&capsule, at(0., 0.), &circle, at(15., 0.)));
+        assert!(!touches(&capsule, at(0., 0.), &circle, at(15.5, 0.)));
+        // beside it anywhere along the way
+        assert!(touches(&capsule, at(0., 0.), &circle, at(-7., 5.)));
+        assert!(!touches(&capsule, at(0., 0.), &circle, at(-7., 5.5)));
+    }
+
+    #[test]
+    fn lasers_turn_with_their_entity() {
+        let laser = Collider::capsule(Vec2::new(0., 10.), 2.);
+        let target = Collider::circle(1.);
+        let turned =
+            |x, y| at(x, y).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        assert!(touches(&laser, at(0., 0.), &target, at(0., 12.)));
+        assert!(!touches(&laser, at(0., 0.), &target, at(12., 0.)));
+        assert!(touches(&laser, turned(0., 0.), &target, at(12., 0.)));
+        assert!(!touches(&laser, turned(0., 0.), &target, at(0., 12.)));
+        // only rotation counts, sprites are scaled all over the place
+        let scaled = at(0., 0.).with_scale(Vec3::splat(3.));
+        assert!(!touches(&laser, scaled, &target, at(0., 20.)));
+    }
+
+    #[test]
+    fn compound_parts_sit_at_their_offsets() {
+        let hull = Collider::circle(4.)
+            .with(Vec2::new(20., 0.), Collider::circle(2.))
+            .with(Vec2::new(-20., 0.), Collider::circle(2.));
+        let target = Collider::circle(1.);
+        assert!(touches(&hull, at(0., 0.), &target, at(22., 0.)));
+        assert!(touches(&hull, at(0., 0.), &target, at(-22., 0.)));
+        // nothing in the gaps between the parts
+        assert!(!touches(&hull, at(0., 0.), &target, at(12., 0.)));
+        // offsets turn with the entity too
+        let turned = at(0., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        assert!(touches(&hull, turned, &target, at(0., 22.)));
+        assert!(!touches(&hull, turned, &target, at(22., 0.)));
+    }
+
+    #[test]
+    fn fit_lies_along_the_longer_side() {
+        let segment = |collider: Collider| collider.0[0].shape.segment();
+        assert_eq!(
+            segment(Collider::fit(Vec2::new(10., 30.))),
+            (Vec2::new(0., 10.), 5.)
+        );
+        assert_eq!(
+            segment(Collider::fit(Vec2::new(30., 10.))),
+            (Vec2::new(10., 0.), 5.)
+        );
+        assert_eq!(segment(Collider::fit(Vec2::splat(10.))), (Vec2::ZERO, 5.));
+    }
+
+    #[test]
+    fn pairs_share_a_cell_and_come_once() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(SpawnIndex);
+        let mut spatial_hash = SpatialHash::new(10.);
+        // `a` spans four cells, `b` shares two of them
+        spatial_hash.insert(a, Vec2::new(5., 5.), Vec2::new(15., 15.));
+        spatial_hash.insert(b, Vec2::new(5., 12.), Vec2::new(15., 18.));
+        // `c` is only in a corner cell of `a`
+        spatial_hash.insert(c, Vec2::new(-5., -5.), Vec2::new(2., 2.));
+        // `d` is only near them
+        spatial_hash.insert(d, Vec2::new(21., 21.), Vec2::new(25., 25.));
+        let pairs: Vec<_> = spatial_hash.pairs().into_iter().collect();
+        assert_eq!(pairs, vec![(a, b), (a, c)]);
+    }
+
+    #[test]
+    fn layers_interact_both_ways() {
+        let player = CollisionLayers::player();
+        assert!(player.interacts(CollisionLayers::enemy()));
+        assert!(player.interacts(CollisionLayers::enemy_laser()));
+        assert!(player.interacts(CollisionLayers::pickup()));
+        assert!(!player.interacts(CollisionLayers::player_laser()));
+        assert!(!CollisionLayers::enemy().interacts(CollisionLayers::pickup()));
+        assert!(!CollisionLayers::player_laser()
+            .interacts(CollisionLayers::enemy_laser()));
+
+        // looking for the other side isn't enough, it has to look back
+        let one_sided = CollisionLayers::new(CollisionLayers::ENEMY, 0);
+        assert!(!CollisionLayers::player().interacts(one_sided));
+        assert!(!one_sided.interacts(CollisionLayers::player()));
+    }
+
+    fn at(x: f32, y: f32) -> Transform {
+        Transform::from_xyz(x, y, 0.)
+    }
+
+    #[test]
+    fn sweeping_catches_what_a_tick_skips_over() {
+        let bullet = Collider::capsule(Vec2::new(0., 4.), 3.);
+        let target = Collider::circle(PLAYER_HURTBOX_RADIUS);
+        // went from 60 above the target to 60 below it in one tick
+        let travel = Vec2::new(0., -120.);
+        assert!(!bullet.intersects_swept(
+            &at(0., -60.),
+            Vec2::ZERO,
+            &target,
+            &at(0., 0.)
+        ));
+        assert!(bullet.intersects_swept(
+            &at(0., -60.),
+            travel,
+            &target,
+            &at(0., 0.)
+        ));
+        // still misses when the whole path does
+        assert!(!bullet.intersects_swept(
+            &at(20., -60.),
+            travel,
+            &target,
+            &at(0., 0.)
+        ));
+    }
+
+    #[test]
+    fn unswept_lasers_cannot_skip_the_hurtbox() {
+        let smallest_laser = Collider::fit(Vec2::new(
+            SIZE_LASER_ENEMY.0.min(SIZE_LASER_ENEMY_ORB.0),
+            SIZE_LASER_ENEMY.1.min(SIZE_LASER_ENEMY_ORB.1),
+        ));
+        let (_, laser_radius) = smallest_laser.0[0].shape.segment();
+        let travel = SWEPT_MIN_SPEED * BASE_SPEED * SIMULATION_STEP;
+        assert!(travel < 2. * (PLAYER_HURTBOX_RADIUS + laser_radius));
+    }
+
+    fn collisions(swept: bool) -> usize {
+        let mut app = App::new();
+        app.insert_resource(SpatialHash::new(COLLISION_CELL_SIZE))
+            .add_event::<CollisionEvent>()
+            .add_system(detect_collisions_system);
+        app.world.spawn((
+            at(0., 0.),
+            SpawnIndex::PLAYER,
+            Collider::circle(PLAYER_HURTBOX_RADIUS),
+            CollisionLayers::player(),
+        ));
+        let mut bullet = app.world.spawn((
+            at(0., 100.),
+            SpawnIndex(1),
+            Collider::circle(3.),
+            CollisionLayers::enemy_laser(),
+        ));
+        if swept {
+            bullet.insert(Swept::default());
+        }
+        let bullet = bullet.id();
+
+        app.update();
+        let mut tf = app.world.get_mut::<Transform>(bullet).unwrap();
+        tf.translation.y = -100.;
+        app.update();
+
+        let events = app.world.resource::<Events<CollisionEvent>>();
+        events.get_reader().iter(events).count()
+    }
+
+    #[test]
+    fn swept_remembers_where_it_was() {
+        assert_eq!(collisions(false), 0);
+        assert_eq!(collisions(true), 1);
+    }
+}
@@ -1,7 +1,13 @@
 use bevy::{
-    prelude::{Component, Vec2},
+    prelude::{Commands, Component, Entity, Transform, Vec2},
     time::{Timer, TimerMode},
 };
+use serde::Deserialize;
+
+use crate::constants::{
+    DASH_COOLDOWN, DASH_TRAIL_INTERVAL, PLAYER_ACCELERATION,
+    PLAYER_DECELERATION, PLAYER_MAX_BANK,
+};
 
 #[derive(Component)]
 pub struct Player;
@@ -9,6 +15,176 @@ pub struct Player;
 #[derive(Component)]
 pub struct Enemy;
 
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum EnemyKind {
+    Drone,
+    Fighter,
+    Gunner,
+    Tank,
+    Boss,
+}
+
+/// A boss enemy, `phase` counts the `BOSS_PHASES` it went through.
+#[derive(Component, Default)]
+pub struct Boss {
+    pub phase: usize,
+}
+
+/// Child hitbox of a boss that multiplies the damage it receives.
+#[derive(Component)]
+pub struct WeakPoint {
+    pub multiplier: i32,
+}
+
+/// A defeated boss going up in waves of explosions before it despawns.
+#[derive(Component)]
+pub struct BossDeath {
+    pub timer: Timer,
+    pub waves_left: u32,
+}
+
+impl Default for BossDeath {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(0.3, TimerMode::Repeating),
+            waves_left: 5,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BossHealthBar;
+
+#[derive(Component)]
+pub struct BossHealthBarFill;
+
+#[derive(Component)]
+pub struct Health(pub i32);
+
+/// Shape of a single enemy volley. Angles are radians.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum FirePattern {
+    /// one shot straight down
+    Straight,
+    /// one shot at the player
+    Aimed,
+    /// `count` shots fanned over `angle`, centered on the player
+    Spread { count: u32, angle: f32 },
+    /// `count` shots evenly around the enemy
+    Ring { count: u32 },
+    /// `count` arms rotating by `step` every volley
+    Spiral { count: u32, step: f32 },
+    /// `shots` aimed shots, `interval` seconds apart
+    Burst { shots: u32, interval: f32 },
+}
+
+#[derive(Component)]
+pub struct EnemyWeapon {
+    pub pattern: FirePattern,
+    pub bullet_speed: f32,
+    pub cooldown: Timer,
+    pub spiral_angle: f32,
+    pub burst_left: u32,
+    pub burst_timer: Timer,
+}
+
+/// Repeating timer that is never zero length, those panic when ticked.
+fn repeating_timer(secs: f32) -> Timer {
+    Timer::from_seconds(secs.max(0.01), TimerMode::Repeating)
+}
+
+impl EnemyWeapon {
+    pub fn new(pattern: FirePattern, interval: f32, bullet_speed: f32) -> Self {
+        let burst_interval = match pattern {
+            FirePattern::Burst { interval, .. } => interval,
+            _ => 1.,
+        };
+        Self {
+            pattern,
+            bullet_speed,
+            cooldown: repeating_timer(interval),
+            spiral_angle: 0.,
+            burst_left: 0,
+            burst_timer: repeating_timer(burst_interval),
+        }
+    }
+}
+
+/// Steers an enemy through screen space waypoints before it drifts down.
+#[derive(Component)]
+pub struct FollowPath {
+    pub waypoints: Vec<Vec2>,
+    pub next: usize,
+    pub speed: f32,
+}
+
+// Movement patterns. Each one steers `Velocity` (in `BASE_SPEED` units) and
+// leaves `movement_system` to actually move the enemy. Distances are pixels,
+// times are seconds.
+
+/// Drifts down while weaving left and right.
+#[derive(Component)]
+pub struct SineWeave {
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub elapsed: f32,
+}
+
+/// Drifts down while cutting sideways, switching direction every `period`.
+#[derive(Component)]
+pub struct ZigZag {
+    pub width: f32,
+    pub period: f32,
+    pub elapsed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwoopPhase {
+    Swoop,
+    Hover,
+    Leave,
+}
+
+/// Swoops in fast, hangs at `hover_y` for `hover_time`, then leaves.
+#[derive(Component)]
+pub struct SwoopHover {
+    pub hover_y: f32,
+    pub hover_time: f32,
+    pub speed: f32,
+    pub phase: SwoopPhase,
+    pub elapsed: f32,
+}
+
+/// Drifts for `delay`, then dives in a straight line at where the player was.
+#[derive(Component)]
+pub struct DiveAtPlayer {
+    pub delay: f32,
+    pub speed: f32,
+    pub elapsed: f32,
+    pub diving: bool,
+}
+
+/// Flies a Catmull-Rom spline through `points` in `duration`.
+#[derive(Component)]
+pub struct SplinePath {
+    pub points: Vec<Vec2>,
+    pub duration: f32,
+    pub speed: f32,
+    pub elapsed: f32,
+}
+
+/// Circles around a slowly descending center for `duration`.
+#[derive(Component)]
+pub struct Orbit {
+    pub center: Vec2,
+    pub radius: f32,
+    pub angular_speed: f32,
+    pub angle: f32,
+    pub duration: f32,
+    pub drift: Vec2,
+    pub elapsed: f32,
+}
+
 #[derive(Component)]
 pub struct Background;
 
@@ -21,6 +197,114 @@ pub struct FromPlayer;
 #[derive(Component)]
 pub struct FromEnemy;
 
+/// Damage a player projectile deals to whatever it hits.
+#[derive(Component)]
+pub struct Damage(pub i32);
+
+/// Keeps a projectile going through `hits_left` more targets, each of them
+/// hit once.
+#[derive(Component)]
+pub struct Pierce {
+    pub hits_left: u32,
+    pub hit: Vec<Entity>,
+}
+
+/// Bubble around the player ship, fading with the charges of the `Shield`.
+#[derive(Component)]
+pub struct ShieldBubble;
+
+/// Sits on the nose of the player ship and grows with `Weapon::charge`.
+#[derive(Component)]
+pub struct ChargeIndicator;
+
+/// Turns the projectile's velocity towards its target, at most `turn_rate`
+/// radians per second.
+#[derive(Component)]
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
+/// Damages everything within `radius` pixels of where the projectile hits.
+#[derive(Component)]
+pub struct Splash {
+    pub radius: f32,
+}
+
+/// Bounces the projectile off the edges of the screen instead of letting it
+/// leave, removed once `bounces_left` runs out.
+#[derive(Component)]
+pub struct Bounce {
+    pub bounces_left: u32,
+}
+
+/// Upgrade levels of the player's gun, weakest first, see `weapons.rs`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeaponLevel {
+    #[default]
+    Single,
+    Double,
+    TripleSpread,
+    WideBeam,
+    HomingMissiles,
+}
+
+/// What holding the fire button does for a weapon.
+#[derive(Debug, Clone, Copy)]
+pub enum FireMode {
+    /// fires whenever the cooldown allows while the button is held
+    Auto,
+    /// a tap fires `shots` volleys `interval` seconds apart, holding only
+    /// charges up a charge shot
+    TapBurst { shots: u32, interval: f32 },
+}
+
+#[derive(Component)]
+pub struct Weapon {
+    pub level: WeaponLevel,
+    /// finished while the weapon is ready to fire
+    pub cooldown: Timer,
+    pub burst_left: u32,
+    pub burst_timer: Timer,
+    /// seconds the fire button has been held, released past
+    /// `CHARGE_THRESHOLD` it fires a charge shot
+    pub charge: f32,
+}
+
+impl Weapon {
+    pub fn new(level: WeaponLevel) -> Self {
+        let definition = level.definition();
+        let mut cooldown =
+            Timer::from_seconds(definition.fire_interval, TimerMode::Once);
+        // ready to fire straight away
+        cooldown.tick(cooldown.duration());
+        let burst_interval = match definition.fire_mode {
+            FireMode::TapBurst { interval, .. } => interval,
+            FireMode::Auto => 1.,
+        };
+        Self {
+            level,
+            cooldown,
+            burst_left: 0,
+            burst_timer: repeating_timer(burst_interval),
+            charge: 0.,
+        }
+    }
+
+    pub fn upgrade(&mut self) {
+        *self = Self::new(self.level.next());
+    }
+
+    pub fn downgrade(&mut self) {
+        *self = Self::new(self.level.previous());
+    }
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self::new(WeaponLevel::default())
+    }
+}
+
 #[derive(Component)]
 pub struct Explosion(pub Timer);
 
@@ -47,5 +331,143 @@ impl From<(f32, f32)> for Collision {
     }
 }
 
+/// The player holds the focus button: slower, tighter and the hitbox shows.
+#[derive(Component)]
+pub struct Focused;
+
+#[derive(Component)]
+pub struct HitboxMarker;
+
 #[derive(Component, Default)]
 pub struct UIFont;
+
+/// Hits are ignored until the timer runs out, the ship blinks meanwhile.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn from_seconds(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+
+    /// Makes `entity` invulnerable for `duration` seconds, unless `existing`
+    /// already lasts at least that long.
+    pub fn grant(
+        commands: &mut Commands,
+        entity: Entity,
+        existing: Option<&Invulnerable>,
+        duration: f32,
+    ) {
+        let protected = existing
+            .map(|invulnerable| invulnerable.0.remaining_secs() >= duration)
+            .unwrap_or(false);
+        if !protected {
+            commands.entity(entity).insert(Self::from_seconds(duration));
+        }
+    }
+}
+
+/// How the player ship handles. Rates are in `Velocity` units per second,
+/// the top speed itself is `PlayerState::speed`.
+#[derive(Component)]
+pub struct ShipHandling {
+    pub acceleration: f32,
+    pub deceleration: f32,
+    /// tilt in radians at full sideways speed
+    pub max_bank: f32,
+}
+
+impl Default for ShipHandling {
+    fn default() -> Self {
+        Self {
+            acceleration: PLAYER_ACCELERATION,
+            deceleration: PLAYER_DECELERATION,
+            max_bank: PLAYER_MAX_BANK,
+        }
+    }
+}
+
+/// Dodge move of the player ship, fast and untouchable for a moment.
+#[derive(Component)]
+pub struct Dash {
+    /// finished while a dash is available
+    pub cooldown: Timer,
+    /// seconds left of the current dash
+    pub remaining: f32,
+    pub direction: Vec2,
+    /// drops an afterimage every time it finishes
+    pub trail: Timer,
+}
+
+impl Dash {
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.
+    }
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(DASH_COOLDOWN, TimerMode::Once);
+        // ready to dash straight away
+        cooldown.tick(cooldown.duration());
+        Self {
+            cooldown,
+            remaining: 0.,
+            direction: Vec2::Y,
+            trail: Timer::from_seconds(
+                DASH_TRAIL_INTERVAL,
+                TimerMode::Repeating,
+            ),
+        }
+    }
+}
+
+/// Fading copy of the player ship left behind by a dash.
+#[derive(Component)]
+pub struct Afterimage(pub Timer);
+
+/// When the entity was spawned in the run, handed out by `SpawnCounter`.
+/// Contacts and hits are resolved in this order, `Entity` ids also depend
+/// on everything spawned and despawned between ticks.
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct SpawnIndex(pub u64);
+
+impl SpawnIndex {
+    /// the player is spawned before the first tick, it simply goes first
+    pub const PLAYER: Self = Self(0);
+}
+
+/// Where the last two simulation ticks left the entity, it's drawn in
+/// between them.
+#[derive(Component)]
+pub struct Interpolated {
+    pub previous: Transform,
+    pub current: Transform,
+}
+
+/// Full screen flash of a bomb, fading out with the timer.
+#[derive(Component)]
+pub struct BombFlash(pub Timer);
+
+#[derive(Component)]
+pub struct Pickup;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    Gold(i32),
+    Health,
+    WeaponUpgrade,
+    Shield,
+    Bomb,
+}
+
+#[derive(Component)]
+pub struct TitleScreen;
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[derive(Component)]
+pub struct PauseScreen;
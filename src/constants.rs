@@ -4,13 +4,75 @@ pub const WINDOW_WIDTH: f32 = 400.;
 pub const WINDOW_HEIGHT: f32 = 600.;
 pub const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
 pub const BASE_SPEED: f32 = 300.;
-pub const MAX_ENEMY_COUNT: i32 = 20;
 pub const EXPLOSION_LENGTH: usize = 16;
-pub const ENEMY_LASER_SPEED_MULTIPLIER: f32 = 1.5;
-pub const UPGRADE_ENEMY_KILL_COUNT: i32 = 10;
+
+pub const SIMULATION_STEP: f32 = 1. / 60.;
+pub const MAX_SIMULATION_LAG: f32 = 0.25;
+pub const INTERPOLATION_SNAP_DISTANCE: f32 = 100.;
+
+pub const COLLISION_CELL_SIZE: f32 = 64.;
+pub const SWEPT_MIN_SPEED: f32 = 0.8;
+
+pub const MAX_HEALTH: i32 = 3;
+pub const HIT_INVULNERABILITY: f32 = 1.2;
+pub const RESPAWN_INVULNERABILITY: f32 = 2.;
+pub const INVULNERABLE_BLINK_INTERVAL: f32 = 0.1;
+pub const PLAYER_ACCELERATION: f32 = 8.;
+pub const PLAYER_DECELERATION: f32 = 6.;
+pub const PLAYER_MAX_BANK: f32 = 0.3;
+pub const PLAYER_HURTBOX_RADIUS: f32 = 4.;
+
+pub const FOCUS_SPEED_SCALE: f32 = 0.45;
+pub const FOCUS_SPREAD_SCALE: f32 = 0.4;
+pub const HITBOX_MARKER_COLOR: Color = Color::rgba(1., 0.3, 0.3, 0.9);
+
+pub const DASH_TIME: f32 = 0.15;
+pub const DASH_COOLDOWN: f32 = 1.2;
+pub const DASH_SPEED_MULTIPLIER: f32 = 4.;
+pub const DASH_TRAIL_INTERVAL: f32 = 0.03;
+pub const AFTERIMAGE_TIME: f32 = 0.25;
+
+pub const SHIELD_BASE_CAPACITY: i32 = 1;
+pub const MAX_SHIELD_CAPACITY: i32 = 3;
+pub const SHIELD_REGEN_DELAY: f32 = 4.;
+pub const SHIELD_REGEN_INTERVAL: f32 = 1.5;
+pub const SHIELD_BUBBLE_RADIUS: f32 = 30.;
+pub const SHIELD_COLOR: Color = Color::rgba(0.4, 0.7, 1., 0.35);
+
+pub const MAX_BOMBS: i32 = 3;
+pub const PLAYER_START_BOMBS: i32 = 1;
+pub const BOMB_DAMAGE: i32 = 5;
+pub const BOMB_INVULNERABILITY: f32 = 1.5;
+pub const BOMB_FLASH_TIME: f32 = 0.4;
+pub const BOMB_FLASH_ALPHA: f32 = 0.8;
+
+pub const MISSILE_TURN_RATE: f32 = 4.;
+pub const MISSILE_SPLASH_RADIUS: f32 = 40.;
+pub const CHARGE_THRESHOLD: f32 = 0.5;
+pub const MAX_CHARGE_TIME: f32 = 1.5;
+pub const CHARGE_SHOT_DAMAGE: i32 = 2;
+pub const CHARGE_DAMAGE_SCALE: f32 = 2.;
+pub const CHARGE_SIZE_SCALE: f32 = 1.5;
+pub const CHARGE_SHOT_COLOR: Color = Color::rgb(0.5, 0.9, 1.);
 
 pub const ENEMY_BASE_VELOCITY: Vec2 = Vec2::new(0., -0.3);
-pub const ENEMY_BASE_FIRE_RATE: f64 = 1. / 80.;
+pub const ENEMY_OFFSCREEN_MARGIN: f32 = 100.;
+pub const PATH_WAYPOINT_RADIUS: f32 = 8.;
+pub const SWOOP_SPEED_MULTIPLIER: f32 = 3.;
+
+pub const BOSS_WAVE_INTERVAL: u32 = 4;
+pub const BOSS_WEAK_POINTS: [(f32, f32); 2] = [(-36., 22.), (36., 22.)];
+pub const BOSS_WEAK_POINT_MULTIPLIER: i32 = 3;
+pub const SIZE_BOSS_WEAK_POINT: (f32, f32) = (16., 16.);
+pub const BOSS_RAM_DAMAGE: i32 = 5;
+pub const BOSS_SWAY_AMPLITUDE: f32 = 110.;
+pub const BOSS_SWAY_FREQUENCY: f32 = 0.15;
+pub const BOSS_DEATH_BLASTS: usize = 5;
+
+pub const PICKUP_VELOCITY: Vec2 = Vec2::new(0., -0.25);
+pub const PICKUP_COLLECT_RADIUS: f32 = 26.;
+pub const PICKUP_SCATTER: f32 = 12.;
+
 pub const BACKGROUND_BASE_SPEED: f32 = 20.;
 
 pub const SPRITE_SCALE: f32 = 1.;
@@ -18,8 +80,24 @@ pub const SPRITE_PLAYER_SHIP: &str = "ships/6.png";
 pub const SIZE_PLAYER_SHIP: (f32, f32) = (44., 36.);
 pub const SPRITE_LASER_PLAYER: &str = "shots/6.png";
 pub const SIZE_LASER_PLAYER: (f32, f32) = (18., 21.);
+pub const SPRITE_BEAM_PLAYER: &str = "shots/11.png";
+pub const SIZE_BEAM_PLAYER: (f32, f32) = (19., 47.);
+pub const SPRITE_MISSILE_PLAYER: &str = "shots/3.png";
+pub const SIZE_MISSILE_PLAYER: (f32, f32) = (8., 17.);
 pub const SPRITE_LASER_ENEMY: &str = "shots/2.png";
 pub const SIZE_LASER_ENEMY: (f32, f32) = (8., 17.);
+pub const SPRITE_LASER_ENEMY_NEEDLE: &str = "shots/7.png";
+pub const SIZE_LASER_ENEMY_NEEDLE: (f32, f32) = (12., 17.);
+pub const SPRITE_LASER_ENEMY_ORB: &str = "shots/4.png";
+pub const SIZE_LASER_ENEMY_ORB: (f32, f32) = (10., 10.);
+pub const SPRITE_LASER_ENEMY_HEAVY: &str = "shots/8.png";
+pub const SIZE_LASER_ENEMY_HEAVY: (f32, f32) = (36., 44.);
+pub const SPRITE_PICKUP_GOLD: &str = "shots/4.png";
+pub const SPRITE_PICKUP_HEALTH: &str = "shots/5.png";
+pub const SPRITE_PICKUP_WEAPON: &str = "shots/9.png";
+pub const SPRITE_PICKUP_SHIELD: &str = "shots/10.png";
+pub const SPRITE_PICKUP_BOMB: &str = "shots/12.png";
+pub const SIZE_PICKUP: (f32, f32) = (18., 18.);
 pub const SPRITE_SHEET_EXPLOSION: &str = "effects/explo_a_sheet.png";
 
 pub const SPRITE_BACKGROUND: &str = "backgrounds/1_vertical.png";
@@ -34,6 +112,6 @@ pub const AUDIO_EXPLOSION: &str = "audio/explosion.ogg";
 pub const AUDIO_DEATH: &str = "audio/death.ogg";
 pub const AUDIO_POWERUP: &str = "audio/powerup.ogg";
 pub const AUDIO_GOLD: &str = "audio/gold.ogg";
+pub const STAGE_WAVES: &str = "waves/endless.stage.ron";
 pub const THEME_SONG: &str = "music/1.ogg";
 pub const GAME_FONT: &str = "fonts/nasalization-rg.otf";
-
@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct DashPlugin;
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_systems(
+            SimulationStep::Steering,
+            SystemSet::new().with_system(player_dash_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(latch_dash_button_system)
+                .with_system(afterimage_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Playing)
+                .with_system(despawn_with::<Afterimage>),
+        );
+    }
+}
+
+fn dash_button(
+    keyboard: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+) -> bool {
+    keyboard.just_pressed(KeyCode::A)
+        || gamepads.iter().any(|gamepad| {
+            buttons.just_pressed(GamepadButton::new(
+                gamepad,
+                GamepadButtonType::East,
+            ))
+        })
+}
+
+fn latch_dash_button_system(
+    mut input_latch: ResMut<InputLatch>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    input_latch.dash |= dash_button(&keyboard, &gamepads, &buttons);
+}
+
+fn player_dash_system(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &Transform, &Velocity, &mut Dash, Option<&Invulnerable>),
+        With<Player>,
+    >,
+    player_state: Res<PlayerState>,
+    mut input_latch: ResMut<InputLatch>,
+    game_textures: Res<GameTextures>,
+    time: Res<SimulationTime>,
+) {
+    // a press while the dash can't go is dropped, not queued
+    let pressed = std::mem::take(&mut input_latch.dash);
+    let (entity, tf, velocity, mut dash, invulnerable) =
+        match query.get_single_mut() {
+            Ok(player) => player,
+            Err(_) => return,
+        };
+
+    dash.cooldown.tick(time.delta());
+    if dash.is_active() {
+        dash.remaining -= time.delta_seconds();
+        if dash.trail.tick(time.delta()).just_finished() {
+            commands.spawn((
+                SpriteBundle {
+                    texture: game_textures.player.clone(),
+                    sprite: Sprite {
+                        color: Color::rgba(0.6, 0.8, 1., 0.5),
+                        ..default()
+                    },
+                    // just behind the ship
+                    transform: tf.with_translation(
+                        tf.translation - Vec3::new(0., 0., 1.),
+                    ),
+                    ..default()
+                },
+                Afterimage(Timer::from_seconds(
+                    AFTERIMAGE_TIME,
+                    TimerMode::Once,
+                )),
+            ));
+        }
+        return;
+    }
+
+    if !player_state.is_alive
+        || !dash.cooldown.finished()
+        || !pressed
+    {
+        return;
+    }
+
+    // standing still dashes forward
+    let direction = velocity.0.normalize_or_zero();
+    dash.direction = if direction == Vec2::ZERO {
+        Vec2::Y
+    } else {
+        direction
+    };
+    dash.remaining = DASH_TIME;
+    dash.trail.reset();
+    dash.cooldown.reset();
+
+    Invulnerable::grant(&mut commands, entity, invulnerable, DASH_TIME);
+}
+
+fn afterimage_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Afterimage, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut afterimage, mut sprite) in query.iter_mut() {
+        afterimage.0.tick(time.delta());
+        sprite.color.set_a(0.5 * afterimage.0.percent_left());
+        if afterimage.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
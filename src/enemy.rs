@@ -1,11 +1,15 @@
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    f32::consts::{FRAC_PI_2, TAU},
+    time::Duration,
+};
 
+use crate::pickups::spawn_pickup;
+use crate::player::spawn_player_laser_system;
 use crate::prelude::*;
-use bevy::{
-    ecs::schedule::ShouldRun, prelude::*, sprite::collide_aabb::collide,
-    time::FixedTimestep,
-};
-use rand::{thread_rng, Rng};
+use crate::projectiles::{resolve_impact, ProjectileBehaviour};
+use bevy::prelude::*;
+use rand::Rng;
 
 pub struct EnemyPlugin;
 
@@ -14,158 +18,294 @@ impl Plugin for EnemyPlugin {
         app.insert_resource(EnemyCount(0))
             .insert_resource(EnemyAttributes::default())
             .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_enemies_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Fire,
+                // both spawn with the `SpawnCounter`, the player goes first
+                SystemSet::new().with_system(
+                    enemy_fire_system.after(spawn_player_laser_system),
+                ),
+            )
+            .add_simulation_systems(
+                SimulationStep::Hits,
                 SystemSet::new()
-                    .with_run_criteria(FixedTimestep::step(1.))
-                    .with_system(spawn_enemy_system),
+                    .with_system(enemy_laser_hit_player_system)
+                    .with_system(handle_enemy_out_of_bounds_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Damage,
+                SystemSet::new().with_system(handle_enemy_take_hit_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new().with_system(enemy_fire_sound_system),
             )
             .add_system_set(
-                SystemSet::new()
-                    .with_run_criteria(enemy_fire_criteria)
-                    .with_system(enemy_fire_system),
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(despawn_with::<Enemy>),
             )
-            .add_system(enemy_laser_hit_player_system)
-            .add_system(handle_enemy_out_of_bounds_system)
-            .add_system(handle_enemy_take_hit_system)
-            .add_event::<EnemyLaserFireEvent>()
-            .add_event::<EnemyTakeHitEvent>()
-            .add_event::<PlayerTakeHitEvent>();
+            .add_simulation_event::<EnemyLaserFireEvent>()
+            .add_simulation_event::<EnemyTakeHitEvent>()
+            .add_simulation_event::<PlayerTakeHitEvent>();
     }
 }
 
-fn get_random_enemy_position(
-    window_size: Vec2,
-    enemy_positions: &[Vec2],
-) -> (f32, f32) {
-    let mut rng = thread_rng();
-    let enemy_x = rng.gen_range(
-        (-window_size.x / 2. + SIZE_ENEMY_SHIP.0 / 2.)
-            ..(window_size.x / 2. - SIZE_ENEMY_SHIP.0 / 2.),
+fn reset_enemies_system(
+    mut enemy_count: ResMut<EnemyCount>,
+    mut enemy_attrs: ResMut<EnemyAttributes>,
+) {
+    enemy_count.0 = 0;
+    enemy_attrs.reset();
+}
+
+/// Weapon of a fresh enemy, its first volley staggered so a formation
+/// doesn't fire in lockstep.
+pub fn enemy_weapon(
+    definition: &EnemyDefinition,
+    pattern: FirePattern,
+    rng: &mut GameRng,
+) -> EnemyWeapon {
+    let mut weapon = EnemyWeapon::new(
+        pattern,
+        definition.fire_interval,
+        definition.bullet_speed,
     );
-    let enemy_y = (rng.gen_range(10..50) as f32) + window_size.y / 2.;
-    for enemy_pos in enemy_positions.iter() {
-        let collision = collide(
-            Vec3::new(enemy_pos.x, enemy_pos.y, 0.),
-            Vec2::from(SIZE_ENEMY_SHIP),
-            Vec3::new(enemy_x, enemy_y, 0.),
-            Vec2::from(SIZE_ENEMY_SHIP),
-        );
-        if collision.is_some() {
-            return get_random_enemy_position(window_size, &enemy_positions);
-        }
-    }
-    (enemy_x, enemy_y)
+    let head_start = rng.gen_range(0. ..definition.fire_interval);
+    weapon
+        .cooldown
+        .set_elapsed(Duration::from_secs_f32(head_start));
+    weapon
 }
 
-fn spawn_enemy_system(
-    mut commands: Commands,
-    mut enemy_count: ResMut<EnemyCount>,
-    game_textures: Res<GameTextures>,
-    window_size: Res<WindowSize>,
-    enemy_attrs: Res<EnemyAttributes>,
-    query: Query<&Transform, With<Enemy>>,
-) {
-    if enemy_count.0 >= MAX_ENEMY_COUNT {
-        return;
-    }
+/// Spawns a single enemy of `kind`, moving with `velocity` (in `BASE_SPEED`
+/// units). Callers are responsible for keeping `EnemyCount` in sync.
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    rng: &mut GameRng,
+    spawn_counter: &mut SpawnCounter,
+    kind: EnemyKind,
+    position: Vec2,
+    velocity: Vec2,
+) -> Entity {
+    let definition = kind.definition();
+    let scale = SPRITE_SCALE * definition.scale;
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: game_textures.enemies[&kind].clone(),
+                sprite: Sprite {
+                    color: definition.color,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(position.x, position.y, 1.),
+                    scale: Vec3::new(scale, -scale, 1.),
+                    ..default()
+                },
+                ..default()
+            },
+            Enemy,
+            kind,
+            spawn_counter.next(),
+            Health(definition.health),
+            Collision(definition.hitbox()),
+            Collider::fit(definition.hitbox()),
+            CollisionLayers::enemy(),
+            Movable { auto_despawn: true },
+            Velocity(velocity),
+            enemy_weapon(definition, definition.fire_pattern, rng),
+        ))
+        .id()
+}
 
-    let mut enemy_positions: Vec<Vec2> = vec![];
-    for enemy_tf in query.iter() {
-        enemy_positions
-            .push(Vec2::new(enemy_tf.translation.x, enemy_tf.translation.y));
+/// Directions of the bullets of a single volley, `aim` pointing at the player.
+fn volley_directions(
+    pattern: FirePattern,
+    aim: Vec2,
+    spiral: f32,
+) -> Vec<Vec2> {
+    let aim_angle = aim.y.atan2(aim.x);
+    let around = |count: u32, start: f32| -> Vec<Vec2> {
+        (0..count)
+            .map(|i| start + TAU * i as f32 / count as f32)
+            .map(|angle| Vec2::new(angle.cos(), angle.sin()))
+            .collect()
+    };
+    match pattern {
+        FirePattern::Straight => vec![Vec2::NEG_Y],
+        FirePattern::Aimed | FirePattern::Burst { .. } => vec![aim],
+        FirePattern::Spread { count, angle } => (0..count)
+            .map(|i| {
+                let t = if count > 1 {
+                    i as f32 / (count - 1) as f32 - 0.5
+                } else {
+                    0.
+                };
+                let angle = aim_angle + angle * t;
+                Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect(),
+        FirePattern::Ring { count } => around(count, aim_angle),
+        FirePattern::Spiral { count, .. } => around(count, spiral),
     }
+}
 
-    let (enemy_x, enemy_y) = get_random_enemy_position(
-        Vec2::new(window_size.width, window_size.height),
-        &enemy_positions,
-    );
-
-    commands.spawn((
+fn spawn_enemy_laser(
+    commands: &mut Commands,
+    spawn_counter: &mut SpawnCounter,
+    texture: Handle<Image>,
+    size: (f32, f32),
+    behaviour: &ProjectileBehaviour,
+    position: Vec3,
+    velocity: Vec2,
+) {
+    // laser sprites point down, turn them to face where they are going
+    let rotation = velocity.y.atan2(velocity.x) + FRAC_PI_2;
+    let mut laser = commands.spawn((
         SpriteBundle {
-            texture: game_textures.enemy.clone(),
+            texture,
             transform: Transform {
-                translation: Vec3::new(enemy_x, enemy_y, 1.),
-                scale: Vec3::new(SPRITE_SCALE, -SPRITE_SCALE, 1.),
-                ..default()
+                translation: Vec3::new(position.x, position.y, 1.),
+                rotation: Quat::from_rotation_z(rotation),
+                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
             },
             ..default()
         },
-        Enemy,
-        Collision::from(SIZE_ENEMY_SHIP),
+        Laser,
+        FromEnemy,
+        spawn_counter.next(),
         Movable { auto_despawn: true },
-        Velocity(enemy_attrs.velocity), //  -.1
+        Collision::from(size),
+        Collider::fit(Vec2::new(size.0, size.1)),
+        CollisionLayers::enemy_laser(),
+        Velocity(velocity),
     ));
-
-    enemy_count.0 += 1;
-}
-
-fn enemy_fire_criteria(enemy_attrs: Res<EnemyAttributes>) -> ShouldRun {
-    if rand::thread_rng().gen_bool(enemy_attrs.fire_rate) {
-        ShouldRun::Yes
-    } else {
-        ShouldRun::No
+    if velocity.length() >= SWEPT_MIN_SPEED {
+        laser.insert(Swept::default());
     }
+    behaviour.insert(&mut laser);
 }
 
 fn enemy_fire_system(
     mut commands: Commands,
-    enemy_attrs: Res<EnemyAttributes>,
+    mut fire_event: EventWriter<EnemyLaserFireEvent>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    mut query: Query<(&Transform, &EnemyKind, &mut EnemyWeapon), With<Enemy>>,
+    query_player: Query<&Transform, With<Player>>,
     game_textures: Res<GameTextures>,
-    query: Query<&Transform, With<Enemy>>,
+    window_size: Res<WindowSize>,
+    time: Res<SimulationTime>,
 ) {
-    for tf in query.iter() {
-        commands.spawn((
-            SpriteBundle {
-                texture: game_textures.laser_enemy.clone(),
-                transform: Transform {
-                    translation: Vec3::new(
-                        tf.translation.x,
-                        tf.translation.y,
-                        1.,
-                    ),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..default()
-                },
-                ..default()
-            },
-            Laser,
-            FromEnemy,
-            Movable { auto_despawn: true },
-            Collision::from(SIZE_LASER_ENEMY),
-            Velocity(Vec2::new(
-                0.,
-                ENEMY_LASER_SPEED_MULTIPLIER * enemy_attrs.velocity.y,
-            )),
-        ));
+    let player = query_player.get_single().ok();
+    for (tf, kind, mut weapon) in query.iter_mut() {
+        // no sniping from above the screen while flying in
+        if tf.translation.y > window_size.height / 2. {
+            continue;
+        }
+
+        let fires = if weapon.cooldown.tick(time.delta()).just_finished() {
+            if let FirePattern::Burst { shots, .. } = weapon.pattern {
+                weapon.burst_left = shots.saturating_sub(1);
+                weapon.burst_timer.reset();
+            }
+            true
+        } else if weapon.burst_left > 0
+            && weapon.burst_timer.tick(time.delta()).just_finished()
+        {
+            weapon.burst_left -= 1;
+            true
+        } else {
+            false
+        };
+        if !fires {
+            continue;
+        }
+
+        let aim = match player {
+            Some(player_tf) => (player_tf.translation - tf.translation)
+                .truncate()
+                .normalize_or_zero(),
+            None => Vec2::NEG_Y,
+        };
+        let definition = kind.definition();
+        for direction in
+            volley_directions(weapon.pattern, aim, weapon.spiral_angle)
+        {
+            spawn_enemy_laser(
+                &mut commands,
+                &mut spawn_counter,
+                game_textures.enemy_lasers[kind].clone(),
+                definition.laser_size,
+                &definition.laser_behaviour,
+                tf.translation,
+                direction * weapon.bullet_speed,
+            );
+        }
+        if let FirePattern::Spiral { step, .. } = weapon.pattern {
+            weapon.spiral_angle += step;
+        }
+        fire_event.send(EnemyLaserFireEvent);
+    }
+}
+
+/// Every volley fired in the same tick shares a single sound.
+fn enemy_fire_sound_system(
+    fire_events: EventReader<EnemyLaserFireEvent>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<Audio>,
+) {
+    if fire_events.is_empty() {
+        return;
     }
+    fire_events.clear();
+    audio.play(audio_assets.enemy_shoot.clone());
 }
 
 fn enemy_laser_hit_player_system(
     mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
     mut player_take_hit_event: EventWriter<PlayerTakeHitEvent>,
-    query_lasers: Query<
-        (Entity, &Transform, &Collision),
+    mut explosion_event: EventWriter<ExplosionEvent>,
+    mut query_lasers: Query<
+        (&Transform, Option<&mut Pierce>, Option<&Splash>),
         (With<Laser>, With<FromEnemy>),
     >,
-    query_player: Query<(&Transform, &Collision), With<Player>>,
+    query_player: Query<(), With<Player>>,
 ) {
     let mut despawned: HashSet<Entity> = HashSet::new();
-    let player = query_player.get_single();
-    if let Ok((player_tf, player_size)) = player {
-        for (laser_entity, laser_tf, laser_size) in query_lasers.iter() {
-            if despawned.contains(&laser_entity) {
-                continue;
-            }
-            let collision = collide(
-                player_tf.translation,
-                player_size.0,
-                laser_tf.translation,
-                laser_size.0,
-            );
-            if collision.is_some() {
-                player_take_hit_event.send_default();
-                commands.entity(laser_entity).despawn_recursive();
-                despawned.insert(laser_entity);
-            }
+    for event in collision_events.iter() {
+        let (laser, player) =
+            match event.either(|entity| query_lasers.contains(entity)) {
+                Some(contact) => contact,
+                None => continue,
+            };
+        if despawned.contains(&laser) || !query_player.contains(player) {
+            continue;
+        }
+        let (laser_tf, mut pierce, splash) = match query_lasers.get_mut(laser)
+        {
+            Ok(laser) => laser,
+            Err(_) => continue,
+        };
+        let spent = match resolve_impact(pierce.as_deref_mut(), player) {
+            Some(spent) => spent,
+            None => continue,
+        };
+
+        player_take_hit_event.send_default();
+        // there is only the player to hit, splash is just for show
+        if splash.is_some() {
+            explosion_event.send(ExplosionEvent {
+                position: laser_tf.translation.truncate(),
+                with_sound: false,
+            });
+        }
+        if spent {
+            commands.entity(laser).despawn_recursive();
+            despawned.insert(laser);
         }
     }
 }
@@ -174,15 +314,20 @@ fn handle_enemy_out_of_bounds_system(
     mut commands: Commands,
     mut out_of_bounds: EventWriter<PlayerTakeHitEvent>,
     mut enemy_count: ResMut<EnemyCount>,
-    query: Query<(Entity, &Transform), With<Enemy>>,
+    query: Query<(Entity, &Transform, &Collision), With<Enemy>>,
     window_size: Res<WindowSize>,
 ) {
-    for (entity, tf) in query.iter() {
-        if tf.translation.y + SIZE_ENEMY_SHIP.1 / 2. < -window_size.height / 2.
-        {
+    for (entity, tf, collision) in query.iter() {
+        if tf.translation.y + collision.0.y / 2. < -window_size.height / 2. {
             out_of_bounds.send_default();
             commands.entity(entity).despawn_recursive();
             enemy_count.0 -= 1;
+        } else if tf.translation.x.abs() - collision.0.x / 2.
+            > window_size.width / 2. + ENEMY_OFFSCREEN_MARGIN
+        {
+            // flew off the side following its path, no harm done
+            commands.entity(entity).despawn_recursive();
+            enemy_count.0 -= 1;
         }
     }
 }
@@ -193,26 +338,71 @@ fn handle_enemy_take_hit_system(
     mut take_hit_events: EventReader<EnemyTakeHitEvent>,
     mut explosion_event: EventWriter<ExplosionEvent>,
     mut player_state: ResMut<PlayerState>,
-    mut wave_complete_event: EventWriter<WaveCompleteEvent>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    mut query: Query<(&mut Health, &EnemyKind, Option<&Boss>), With<Enemy>>,
+    game_textures: Res<GameTextures>,
 ) {
-    let mut despawned: HashSet<Entity> = HashSet::new();
     for event in take_hit_events.iter() {
-        if despawned.contains(&event.0) {
+        let (mut health, kind, boss) = match query.get_mut(event.entity) {
+            Ok(enemy) => enemy,
+            Err(_) => continue,
+        };
+        // already killed by an earlier hit this frame
+        if health.0 <= 0 {
+            continue;
+        }
+
+        health.0 -= event.damage;
+        if health.0 > 0 {
             continue;
         }
 
         explosion_event.send(ExplosionEvent {
-            position: Vec2::new(event.1.x, event.1.y),
+            position: Vec2::new(event.position.x, event.position.y),
             with_sound: true,
         });
         enemy_count.0 -= 1;
-        player_state.increment_score();
+        player_state.add_score(kind.definition().score);
+        player_state.increment_kills();
 
-        if player_state.score % UPGRADE_ENEMY_KILL_COUNT == 0 {
-            wave_complete_event.send_default();
+        for drop in kind.definition().drops.iter() {
+            // the same draws whether it drops or not, so one lucky roll
+            // doesn't shift every roll after it
+            let dropped = rng.gen_bool(drop.chance);
+            // spread multiple drops out a little so they don't stack
+            let scatter = Vec2::new(
+                rng.gen_range(-PICKUP_SCATTER..PICKUP_SCATTER),
+                rng.gen_range(-PICKUP_SCATTER..PICKUP_SCATTER),
+            );
+            if dropped {
+                spawn_pickup(
+                    &mut commands,
+                    &mut spawn_counter,
+                    &game_textures,
+                    drop.kind,
+                    event.position.truncate() + scatter,
+                );
+            }
         }
 
-        despawned.insert(event.0);
-        commands.entity(event.0).despawn_recursive();
+        if boss.is_some() {
+            // bosses go out with a bang, see `boss_death_system`. Nothing
+            // can touch them anymore, weak points included
+            commands
+                .entity(event.entity)
+                .remove::<(
+                    Enemy,
+                    EnemyWeapon,
+                    SineWeave,
+                    SwoopHover,
+                    Collider,
+                    CollisionLayers,
+                )>()
+                .insert(BossDeath::default())
+                .despawn_descendants();
+        } else {
+            commands.entity(event.entity).despawn_recursive();
+        }
     }
 }
@@ -0,0 +1,215 @@
+use crate::prelude::*;
+use crate::projectiles::ProjectileBehaviour;
+use bevy::prelude::{Color, Vec2};
+
+/// A pickup an enemy can leave behind, rolled once per kill.
+#[derive(Debug)]
+pub struct DropTableEntry {
+    pub kind: PickupKind,
+    pub chance: f64,
+}
+
+/// Everything that makes an enemy kind play differently. The only enemy ship
+/// art we have is `SPRITE_ENEMY_SHIP`, so kinds tell themselves apart with a
+/// tint, a scale and the shot they fire.
+#[derive(Debug)]
+pub struct EnemyDefinition {
+    pub health: i32,
+    pub sprite: &'static str,
+    pub size: (f32, f32),
+    pub scale: f32,
+    pub color: Color,
+    /// multiplier on top of `EnemyAttributes::velocity`
+    pub speed: f32,
+    /// seconds between volleys
+    pub fire_interval: f32,
+    pub fire_pattern: FirePattern,
+    /// in `BASE_SPEED` units, independent of how fast the ship moves
+    pub bullet_speed: f32,
+    pub laser_sprite: &'static str,
+    pub laser_size: (f32, f32),
+    pub laser_behaviour: ProjectileBehaviour,
+    pub score: i32,
+    pub drops: &'static [DropTableEntry],
+}
+
+impl EnemyDefinition {
+    pub fn hitbox(&self) -> Vec2 {
+        Vec2::new(self.size.0, self.size.1) * self.scale
+    }
+}
+
+static DRONE: EnemyDefinition = EnemyDefinition {
+    health: 1,
+    sprite: SPRITE_ENEMY_SHIP,
+    size: SIZE_ENEMY_SHIP,
+    scale: 0.8,
+    color: Color::WHITE,
+    speed: 1.,
+    fire_interval: 1.6,
+    fire_pattern: FirePattern::Straight,
+    bullet_speed: 0.6,
+    laser_sprite: SPRITE_LASER_ENEMY,
+    laser_size: SIZE_LASER_ENEMY,
+    laser_behaviour: ProjectileBehaviour::NONE,
+    score: 1,
+    drops: &[DropTableEntry {
+        kind: PickupKind::Gold(1),
+        chance: 0.15,
+    }],
+};
+
+static FIGHTER: EnemyDefinition = EnemyDefinition {
+    health: 2,
+    sprite: SPRITE_ENEMY_SHIP,
+    size: SIZE_ENEMY_SHIP,
+    scale: 0.9,
+    color: Color::rgb(1., 0.55, 0.55),
+    speed: 1.6,
+    fire_interval: 2.,
+    fire_pattern: FirePattern::Burst {
+        shots: 3,
+        interval: 0.12,
+    },
+    bullet_speed: 0.9,
+    laser_sprite: SPRITE_LASER_ENEMY_NEEDLE,
+    laser_size: SIZE_LASER_ENEMY_NEEDLE,
+    laser_behaviour: ProjectileBehaviour::NONE,
+    score: 2,
+    drops: &[
+        DropTableEntry {
+            kind: PickupKind::Gold(1),
+            chance: 0.25,
+        },
+        DropTableEntry {
+            kind: PickupKind::Health,
+            chance: 0.03,
+        },
+    ],
+};
+
+static GUNNER: EnemyDefinition = EnemyDefinition {
+    health: 3,
+    sprite: SPRITE_ENEMY_SHIP,
+    size: SIZE_ENEMY_SHIP,
+    scale: 1.,
+    color: Color::rgb(0.55, 1., 0.6),
+    speed: 0.8,
+    fire_interval: 2.2,
+    fire_pattern: FirePattern::Spread {
+        count: 3,
+        angle: 0.6,
+    },
+    bullet_speed: 0.6,
+    laser_sprite: SPRITE_LASER_ENEMY_ORB,
+    laser_size: SIZE_LASER_ENEMY_ORB,
+    laser_behaviour: ProjectileBehaviour {
+        bounces: 1,
+        ..ProjectileBehaviour::NONE
+    },
+    score: 3,
+    drops: &[
+        DropTableEntry {
+            kind: PickupKind::Gold(2),
+            chance: 0.3,
+        },
+        DropTableEntry {
+            kind: PickupKind::WeaponUpgrade,
+            chance: 0.08,
+        },
+        DropTableEntry {
+            kind: PickupKind::Shield,
+            chance: 0.05,
+        },
+    ],
+};
+
+static TANK: EnemyDefinition = EnemyDefinition {
+    health: 8,
+    sprite: SPRITE_ENEMY_SHIP,
+    size: SIZE_ENEMY_SHIP,
+    scale: 1.4,
+    color: Color::rgb(0.75, 0.6, 1.),
+    speed: 0.5,
+    fire_interval: 2.5,
+    fire_pattern: FirePattern::Ring { count: 12 },
+    bullet_speed: 0.5,
+    laser_sprite: SPRITE_LASER_ENEMY_HEAVY,
+    laser_size: SIZE_LASER_ENEMY_HEAVY,
+    laser_behaviour: ProjectileBehaviour {
+        homing: 0.6,
+        ..ProjectileBehaviour::NONE
+    },
+    score: 5,
+    drops: &[
+        DropTableEntry {
+            kind: PickupKind::Gold(5),
+            chance: 0.8,
+        },
+        DropTableEntry {
+            kind: PickupKind::WeaponUpgrade,
+            chance: 0.2,
+        },
+        DropTableEntry {
+            kind: PickupKind::Bomb,
+            chance: 0.15,
+        },
+    ],
+};
+
+static BOSS: EnemyDefinition = EnemyDefinition {
+    health: 120,
+    sprite: SPRITE_ENEMY_SHIP,
+    size: SIZE_ENEMY_SHIP,
+    scale: 2.5,
+    color: Color::rgb(1., 0.7, 0.35),
+    speed: 1.,
+    fire_interval: 1.2,
+    fire_pattern: FirePattern::Spread {
+        count: 5,
+        angle: 1.,
+    },
+    bullet_speed: 0.7,
+    laser_sprite: SPRITE_LASER_ENEMY_ORB,
+    laser_size: SIZE_LASER_ENEMY_ORB,
+    laser_behaviour: ProjectileBehaviour::NONE,
+    score: 50,
+    drops: &[
+        DropTableEntry {
+            kind: PickupKind::Gold(20),
+            chance: 1.,
+        },
+        DropTableEntry {
+            kind: PickupKind::Health,
+            chance: 1.,
+        },
+        DropTableEntry {
+            kind: PickupKind::WeaponUpgrade,
+            chance: 1.,
+        },
+        DropTableEntry {
+            kind: PickupKind::Bomb,
+            chance: 1.,
+        },
+    ],
+};
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 5] = [
+        EnemyKind::Drone,
+        EnemyKind::Fighter,
+        EnemyKind::Gunner,
+        EnemyKind::Tank,
+        EnemyKind::Boss,
+    ];
+
+    pub fn definition(self) -> &'static EnemyDefinition {
+        match self {
+            EnemyKind::Drone => &DRONE,
+            EnemyKind::Fighter => &FIGHTER,
+            EnemyKind::Gunner => &GUNNER,
+            EnemyKind::Tank => &TANK,
+            EnemyKind::Boss => &BOSS,
+        }
+    }
+}
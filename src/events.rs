@@ -1,16 +1,44 @@
-use bevy::{prelude::*, utils::HashSet};
+use crate::prelude::WeaponLevel;
+use bevy::prelude::*;
+
+/// What the player fired.
+#[derive(Debug, Clone, Copy)]
+pub enum ShotPayload {
+    /// the regular volley of the weapon
+    Volley,
+    /// a single piercing laser, `charge` is seconds the button was held
+    Charged { charge: f32 },
+}
 
-#[derive(Debug, Default)]
-pub struct PlayerLaserFireEvent(pub Vec2);
+/// The player's weapon fired from `position`, the nose of the ship.
+#[derive(Debug)]
+pub struct PlayerLaserFireEvent {
+    pub position: Vec2,
+    pub weapon: WeaponLevel,
+    pub payload: ShotPayload,
+    /// focus mode narrows the spread of the volley
+    pub focused: bool,
+}
 
-#[derive(Debug, Default)]
-pub struct EnemyLaserFireEvent(pub Vec2);
+#[derive(Default)]
+pub struct EnemyLaserFireEvent;
 
 #[derive(Default)]
 pub struct PlayerTakeHitEvent;
 
 #[derive(Debug)]
-pub struct EnemyTakeHitEvent(pub Entity, pub Vec3);
+pub struct EnemyTakeHitEvent {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub damage: i32,
+}
+
+/// Colliders of `a` and `b` touch and their `CollisionLayers` interact.
+#[derive(Debug)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+}
 
 #[derive(Debug)]
 pub struct ExplosionEvent {
@@ -18,14 +46,14 @@ pub struct ExplosionEvent {
     pub with_sound: bool,
 }
 
+/// Explosions going off all at once, only for show. Whatever blew up is
+/// despawned by the system sending it.
 #[derive(Debug)]
 pub struct BatchExplosionsEvent {
-    pub entities: HashSet<Entity>,
     pub positions: Vec<Vec3>,
     pub with_sound: bool,
 }
 
-
 #[derive(Default)]
 pub struct WaveCompleteEvent;
 
@@ -1,18 +1,47 @@
+// systems take whatever they need as arguments, and queries spell out their
+// filters in full
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 use crate::prelude::*;
 use background::BackgroundPlugin;
 use bevy::prelude::*;
+use bomb::BombPlugin;
+use boss::BossPlugin;
+use collision::CollisionPlugin;
+use dash::DashPlugin;
 use enemy::EnemyPlugin;
+use menu::MenuPlugin;
+use patterns::PatternPlugin;
+use pickups::PickupPlugin;
 use player::PlayerPlugin;
+use projectiles::ProjectilePlugin;
+use shield::ShieldPlugin;
+use simulation::SimulationPlugin;
+use waves::{WaveAssets, WavePlugin};
 
 mod components;
 mod background;
+mod bomb;
+mod boss;
+mod collision;
 mod constants;
+mod dash;
 mod enemy;
+mod enemy_kinds;
 mod events;
+mod menu;
+mod patterns;
+mod pickups;
 mod player;
 mod prelude;
+mod projectiles;
 mod resources;
 mod shared;
+mod shield;
+mod simulation;
+mod states;
+mod waves;
+mod weapons;
 
 fn main() {
     App::new()
@@ -26,21 +55,63 @@ fn main() {
             },
             ..default()
         }))
+        .add_state(GameState::Loading)
+        .insert_resource(GameRng::new(seed_from_args()))
         .add_startup_system_set_to_stage(
             StartupStage::PreStartup,
             SystemSet::new().with_system(setup),
         )
+        // first, the other plugins add systems to its stage
+        .add_plugin(SimulationPlugin)
         .add_plugin(EnemyPlugin)
+        .add_plugin(WavePlugin)
+        .add_plugin(PatternPlugin)
+        .add_plugin(BossPlugin)
+        .add_plugin(PickupPlugin)
+        .add_plugin(ProjectilePlugin)
+        .add_plugin(CollisionPlugin)
+        .add_plugin(BombPlugin)
+        .add_plugin(ShieldPlugin)
+        .add_plugin(DashPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(BackgroundPlugin)
         .add_plugin(SharedPlugin)
+        .add_plugin(MenuPlugin)
         .run();
 }
 
+/// `--seed 42` or `--seed=42` replays the same run every time.
+fn seed_from_args() -> Option<u64> {
+    parse_seed(std::env::args().skip(1))
+}
+
+fn parse_seed(mut args: impl Iterator<Item = String>) -> Option<u64> {
+    while let Some(arg) = args.next() {
+        let value = if arg == "--seed" {
+            args.next()
+        } else if let Some(value) = arg.strip_prefix("--seed=") {
+            Some(value.to_owned())
+        } else {
+            continue;
+        };
+        return match value.as_deref().map(str::parse) {
+            Some(Ok(seed)) => Some(seed),
+            // not worth refusing to start over, play a random run instead
+            _ => {
+                eprintln!("--seed needs a number, using a random seed");
+                None
+            }
+        };
+    }
+    None
+}
+
 fn setup(
     mut commands: Commands,
     mut windows: ResMut<Windows>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
 ) {
     commands.spawn(Camera2dBundle::default());
@@ -66,13 +137,45 @@ fn setup(
     let game_textures = GameTextures {
         player: asset_server.load(SPRITE_PLAYER_SHIP),
         laser_player: asset_server.load(SPRITE_LASER_PLAYER),
-        laser_enemy: asset_server.load(SPRITE_LASER_ENEMY),
-        enemy: asset_server.load(SPRITE_ENEMY_SHIP),
+        beam_player: asset_server.load(SPRITE_BEAM_PLAYER),
+        missile_player: asset_server.load(SPRITE_MISSILE_PLAYER),
+        enemies: EnemyKind::ALL
+            .iter()
+            .map(|kind| (*kind, asset_server.load(kind.definition().sprite)))
+            .collect(),
+        enemy_lasers: EnemyKind::ALL
+            .iter()
+            .map(|kind| {
+                (*kind, asset_server.load(kind.definition().laser_sprite))
+            })
+            .collect(),
         background: asset_server.load(SPRITE_BACKGROUND),
         explosion,
+        pickup_gold: asset_server.load(SPRITE_PICKUP_GOLD),
+        pickup_health: asset_server.load(SPRITE_PICKUP_HEALTH),
+        pickup_weapon: asset_server.load(SPRITE_PICKUP_WEAPON),
+        pickup_shield: asset_server.load(SPRITE_PICKUP_SHIELD),
+        pickup_bomb: asset_server.load(SPRITE_PICKUP_BOMB),
     };
     commands.insert_resource(game_textures);
 
+    // shapes drawn over the player, children are scaled with the ship but
+    // the hitbox marker must not be
+    let hitbox_radius = PLAYER_HURTBOX_RADIUS / SPRITE_SCALE;
+    commands.insert_resource(ShapeAssets {
+        shield_bubble: meshes
+            .add(shape::Circle::new(SHIELD_BUBBLE_RADIUS).into()),
+        shield_material: materials.add(ColorMaterial::from(SHIELD_COLOR)),
+        hitbox_marker: meshes.add(shape::Circle::new(hitbox_radius).into()),
+        hitbox_material: materials
+            .add(ColorMaterial::from(HITBOX_MARKER_COLOR)),
+    });
+
+    // load the scripted waves
+    commands.insert_resource(WaveAssets {
+        stage: asset_server.load(STAGE_WAVES),
+    });
+
     // load game audio sources
     let audio_assets = AudioAssets {
         player_shoot: asset_server.load(AUDIO_PLAYER_SHOOT),
@@ -91,3 +194,36 @@ fn setup(
     };
     commands.insert_resource(font_assets);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn seed_in_either_form() {
+        assert_eq!(parse_seed(args(&["--seed", "42"])), Some(42));
+        assert_eq!(parse_seed(args(&["--seed=42"])), Some(42));
+        assert_eq!(parse_seed(args(&["--fullscreen", "--seed=7"])), Some(7));
+    }
+
+    #[test]
+    fn no_seed() {
+        assert_eq!(parse_seed(args(&[])), None);
+        assert_eq!(parse_seed(args(&["--seeds", "42"])), None);
+        assert_eq!(parse_seed(args(&["--seedling=42"])), None);
+    }
+
+    #[test]
+    fn bad_seed_is_random() {
+        assert_eq!(parse_seed(args(&["--seed", "abc"])), None);
+        assert_eq!(parse_seed(args(&["--seed"])), None);
+        assert_eq!(parse_seed(args(&["--seed=-1"])), None);
+    }
+}
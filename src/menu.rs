@@ -0,0 +1,240 @@
+use crate::prelude::*;
+use crate::waves::WaveAssets;
+use bevy::{asset::LoadState, prelude::*};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Loading)
+                .with_system(check_assets_loaded_system),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::Title)
+                .with_system(spawn_title_screen_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Title)
+                .with_system(title_input_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Title)
+                .with_system(despawn_with::<TitleScreen>),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(pause_input_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Paused)
+                .with_system(pause_input_system),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::Paused)
+                .with_system(spawn_pause_screen_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Paused)
+                .with_system(despawn_with::<PauseScreen>),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(spawn_game_over_screen_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::GameOver)
+                .with_system(game_over_input_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::GameOver)
+                .with_system(despawn_with::<GameOverScreen>)
+                .with_system(despawn_with::<Explosion>),
+        );
+    }
+}
+
+fn check_assets_loaded_system(
+    mut state: ResMut<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    game_textures: Res<GameTextures>,
+    font_assets: Res<FontAssets>,
+    wave_assets: Res<WaveAssets>,
+) {
+    let handles = [
+        game_textures.player.id(),
+        game_textures.laser_player.id(),
+        game_textures.beam_player.id(),
+        game_textures.missile_player.id(),
+        game_textures.background.id(),
+        game_textures.pickup_gold.id(),
+        game_textures.pickup_health.id(),
+        game_textures.pickup_weapon.id(),
+        game_textures.pickup_shield.id(),
+        game_textures.pickup_bomb.id(),
+        font_assets.ui.id(),
+        wave_assets.stage.id(),
+    ]
+    .into_iter()
+    .chain(game_textures.enemies.values().map(|handle| handle.id()))
+    .chain(game_textures.enemy_lasers.values().map(|handle| handle.id()));
+    if asset_server.get_group_load_state(handles) == LoadState::Loaded {
+        state.set(GameState::Title).unwrap();
+    }
+}
+
+/// Full screen column that centers its children, shared by the menu screens.
+fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: Color::NONE.into(),
+        ..default()
+    }
+}
+
+fn screen_text(
+    value: impl Into<String>,
+    font_size: f32,
+    font_assets: &FontAssets,
+) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font_assets.ui.clone(),
+            font_size,
+            color: Color::WHITE,
+        },
+    )
+    .with_text_alignment(TextAlignment::CENTER)
+    .with_style(Style {
+        margin: UiRect::all(Val::Px(10.)),
+        ..default()
+    })
+}
+
+fn spawn_title_screen_system(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+) {
+    commands
+        .spawn((screen_root(), TitleScreen))
+        .with_children(|parent| {
+            parent.spawn(screen_text("TOPDOWN SHOOTER", 36., &font_assets));
+            parent.spawn(screen_text(
+                "Press ENTER to start",
+                20.,
+                &font_assets,
+            ));
+        });
+}
+
+fn title_input_system(
+    mut state: ResMut<State<GameState>>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+) {
+    if keyboard.just_pressed(KeyCode::Return) {
+        // the state driver may run the next screen in this very frame
+        keyboard.reset(KeyCode::Return);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Pausing pushes `Paused` on top of `Playing`, so nothing of the running
+/// game is torn down and popping it resumes exactly where it was left.
+fn pause_input_system(
+    mut state: ResMut<State<GameState>>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+) {
+    if !keyboard.any_just_pressed([KeyCode::Escape, KeyCode::P]) {
+        return;
+    }
+    keyboard.reset(KeyCode::Escape);
+    keyboard.reset(KeyCode::P);
+
+    if *state.current() == GameState::Paused {
+        state.pop().unwrap();
+    } else {
+        state.push(GameState::Paused).unwrap();
+    }
+}
+
+fn spawn_pause_screen_system(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..screen_root()
+            },
+            PauseScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(screen_text("PAUSED", 36., &font_assets));
+            parent.spawn(screen_text(
+                "Press ESC or P to resume",
+                20.,
+                &font_assets,
+            ));
+        });
+}
+
+fn spawn_game_over_screen_system(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    player_state: Res<PlayerState>,
+    rng: Res<GameRng>,
+) {
+    commands
+        .spawn((screen_root(), GameOverScreen))
+        .with_children(|parent| {
+            parent.spawn(screen_text("GAME OVER", 36., &font_assets));
+            parent.spawn(screen_text(
+                run_summary(&player_state, rng.seed()),
+                20.,
+                &font_assets,
+            ));
+            parent.spawn(screen_text(
+                "Press ENTER to play again\nPress ESC for title",
+                20.,
+                &font_assets,
+            ));
+        });
+}
+
+fn run_summary(player_state: &PlayerState, seed: u64) -> String {
+    let seconds = player_state.run_time.elapsed().as_secs();
+    format!(
+        "SCORE: {}\nKILLS: {}\nWAVES: {}\nGOLD: {}\nTIME: {:02}:{:02}\n\
+         SEED: {}",
+        player_state.score,
+        player_state.kills,
+        player_state.waves,
+        player_state.golds,
+        seconds / 60,
+        seconds % 60,
+        seed,
+    )
+}
+
+/// The run only restarts once the player confirms, the summary stays up
+/// for as long as they want to look at it.
+fn game_over_input_system(
+    mut state: ResMut<State<GameState>>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+) {
+    if keyboard.just_pressed(KeyCode::Return) {
+        keyboard.reset(KeyCode::Return);
+        state.set(GameState::Playing).unwrap();
+    } else if keyboard.just_pressed(KeyCode::Escape) {
+        keyboard.reset(KeyCode::Escape);
+        state.set(GameState::Title).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use std::f32::consts::TAU;
+
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct PatternPlugin;
+
+impl Plugin for PatternPlugin {
+    fn build(&self, app: &mut App) {
+        // velocities are set before `movement_system` uses them
+        app.add_simulation_systems(
+            SimulationStep::Steering,
+            SystemSet::new()
+                // both steer bosses, swooping in decides whether they sway
+                .with_system(sine_weave_system.after(swoop_hover_system))
+                .with_system(zig_zag_system)
+                .with_system(swoop_hover_system)
+                .with_system(dive_at_player_system)
+                .with_system(spline_path_system)
+                .with_system(orbit_system),
+        );
+    }
+}
+
+/// Velocity (in `BASE_SPEED` units) that lands on `target` after `dt`.
+fn velocity_towards(from: Vec2, target: Vec2, dt: f32) -> Vec2 {
+    if dt <= 0. {
+        return Vec2::ZERO;
+    }
+    (target - from) / dt / BASE_SPEED
+}
+
+pub fn sine_weave_system(
+    mut query: Query<(&mut Velocity, &mut SineWeave, Option<&SwoopHover>)>,
+    time: Res<SimulationTime>,
+) {
+    for (mut velocity, mut weave, swoop) in query.iter_mut() {
+        // still swooping in, it only sways once it hovers
+        if swoop.is_some_and(|swoop| swoop.phase == SwoopPhase::Swoop) {
+            continue;
+        }
+        weave.elapsed += time.delta_seconds();
+        let phase = TAU * weave.frequency * weave.elapsed;
+        velocity.0.x =
+            weave.amplitude * TAU * weave.frequency * phase.cos() / BASE_SPEED;
+    }
+}
+
+fn zig_zag_system(
+    mut query: Query<(&mut Velocity, &mut ZigZag)>,
+    time: Res<SimulationTime>,
+) {
+    for (mut velocity, mut zig_zag) in query.iter_mut() {
+        zig_zag.elapsed += time.delta_seconds();
+        // start half a leg in so the ship cuts around its spawn column
+        let leg = ((zig_zag.elapsed / zig_zag.period) + 0.5) as u32;
+        let direction = if leg.is_multiple_of(2) { 1. } else { -1. };
+        velocity.0.x = direction * zig_zag.width / zig_zag.period / BASE_SPEED;
+    }
+}
+
+fn swoop_hover_system(
+    mut query: Query<(&Transform, &mut Velocity, &mut SwoopHover)>,
+    time: Res<SimulationTime>,
+) {
+    for (tf, mut velocity, mut swoop) in query.iter_mut() {
+        match swoop.phase {
+            SwoopPhase::Swoop => {
+                velocity.0 =
+                    Vec2::new(0., -swoop.speed * SWOOP_SPEED_MULTIPLIER);
+                if tf.translation.y <= swoop.hover_y {
+                    swoop.phase = SwoopPhase::Hover;
+                    velocity.0 = Vec2::ZERO;
+                }
+            }
+            SwoopPhase::Hover => {
+                swoop.elapsed += time.delta_seconds();
+                if swoop.elapsed >= swoop.hover_time {
+                    swoop.phase = SwoopPhase::Leave;
+                    velocity.0 = Vec2::new(0., -swoop.speed);
+                }
+            }
+            SwoopPhase::Leave => {}
+        }
+    }
+}
+
+fn dive_at_player_system(
+    mut query: Query<(&Transform, &mut Velocity, &mut DiveAtPlayer)>,
+    query_player: Query<&Transform, With<Player>>,
+    time: Res<SimulationTime>,
+) {
+    let player = query_player.get_single().ok();
+    for (tf, mut velocity, mut dive) in query.iter_mut() {
+        if dive.diving {
+            continue;
+        }
+        dive.elapsed += time.delta_seconds();
+        if dive.elapsed < dive.delay {
+            continue;
+        }
+
+        // lock on once, a dive is a commitment
+        let direction = match player {
+            Some(player_tf) => (player_tf.translation - tf.translation)
+                .truncate()
+                .normalize_or_zero(),
+            None => Vec2::NEG_Y,
+        };
+        velocity.0 = direction * dive.speed;
+        dive.diving = true;
+    }
+}
+
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2. * p1
+        + (p2 - p0) * t
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+        + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+}
+
+/// Point at `progress` (0..=1) along a spline passing through every point.
+fn sample_spline(points: &[Vec2], progress: f32) -> Vec2 {
+    if points.len() < 2 {
+        return points.first().copied().unwrap_or_default();
+    }
+    let last = points.len() - 1;
+    let scaled = progress.clamp(0., 1.) * last as f32;
+    let i = (scaled as usize).min(last - 1);
+    catmull_rom(
+        points[i.saturating_sub(1)],
+        points[i],
+        points[i + 1],
+        points[(i + 2).min(last)],
+        scaled - i as f32,
+    )
+}
+
+fn spline_path_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &mut Velocity, &mut SplinePath)>,
+    time: Res<SimulationTime>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, tf, mut velocity, mut path) in query.iter_mut() {
+        path.elapsed += dt;
+        if path.elapsed >= path.duration {
+            velocity.0 = Vec2::new(0., -path.speed);
+            commands.entity(entity).remove::<SplinePath>();
+            continue;
+        }
+        let target = sample_spline(&path.points, path.elapsed / path.duration);
+        velocity.0 = velocity_towards(tf.translation.truncate(), target, dt);
+    }
+}
+
+fn orbit_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &mut Velocity, &mut Orbit)>,
+    time: Res<SimulationTime>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, tf, mut velocity, mut orbit) in query.iter_mut() {
+        orbit.elapsed += dt;
+        if orbit.elapsed >= orbit.duration {
+            velocity.0 = orbit.drift;
+            commands.entity(entity).remove::<Orbit>();
+            continue;
+        }
+        let drift = orbit.drift;
+        orbit.center += drift * BASE_SPEED * dt;
+        orbit.angle += orbit.angular_speed * dt;
+        let target = orbit.center
+            + Vec2::new(orbit.angle.cos(), orbit.angle.sin()) * orbit.radius;
+        velocity.0 = velocity_towards(tf.translation.truncate(), target, dt);
+    }
+}
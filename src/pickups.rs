@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_systems(
+            SimulationStep::Hits,
+            SystemSet::new().with_system(collect_pickups_system),
+        )
+        .add_simulation_systems(
+            SimulationStep::Cleanup,
+            SystemSet::new().with_system(despawn_missed_pickups_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Playing)
+                .with_system(despawn_with::<Pickup>),
+        );
+    }
+}
+
+impl PickupKind {
+    /// We have no pickup art, so pickups reuse shot sprites with a tint.
+    fn color(self) -> Color {
+        match self {
+            PickupKind::Gold(_) => Color::rgb(1., 0.85, 0.2),
+            PickupKind::Health => Color::rgb(0.3, 1., 0.4),
+            PickupKind::WeaponUpgrade => Color::rgb(0.4, 0.9, 1.),
+            PickupKind::Shield => Color::rgb(0.4, 0.5, 1.),
+            PickupKind::Bomb => Color::rgb(1., 0.35, 0.3),
+        }
+    }
+}
+
+/// Spawns a pickup drifting down from `position`.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    spawn_counter: &mut SpawnCounter,
+    game_textures: &GameTextures,
+    kind: PickupKind,
+    position: Vec2,
+) {
+    commands.spawn((
+        SpriteBundle {
+            texture: game_textures.pickup(kind),
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::new(SIZE_PICKUP.0, SIZE_PICKUP.1)),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 1.),
+            ..default()
+        },
+        Pickup,
+        kind,
+        spawn_counter.next(),
+        Collision::from(SIZE_PICKUP),
+        // the player only gets hit in its core, be generous here
+        Collider::circle(PICKUP_COLLECT_RADIUS),
+        CollisionLayers::pickup(),
+        // enemies die close to the top edge, only despawn below the screen
+        Movable { auto_despawn: false },
+        Velocity(PICKUP_VELOCITY),
+    ));
+}
+
+fn collect_pickups_system(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut player_state: ResMut<PlayerState>,
+    mut shield: ResMut<Shield>,
+    query_pickups: Query<&PickupKind, With<Pickup>>,
+    mut query_weapon: Query<&mut Weapon, With<Player>>,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<Audio>,
+) {
+    for event in collision_events.iter() {
+        let (entity, _) =
+            match event.either(|entity| query_pickups.contains(entity)) {
+                Some(contact) => contact,
+                None => continue,
+            };
+        let (kind, mut weapon) =
+            match (query_pickups.get(entity), query_weapon.get_single_mut()) {
+                (Ok(kind), Ok(weapon)) => (kind, weapon),
+                _ => continue,
+            };
+
+        // maxed out upgrades are simply wasted
+        match *kind {
+            PickupKind::Gold(amount) => player_state.add_gold(amount),
+            PickupKind::Health => player_state.increment_health(),
+            PickupKind::WeaponUpgrade => weapon.upgrade(),
+            PickupKind::Shield => shield.upgrade(),
+            PickupKind::Bomb => player_state.add_bomb(),
+        }
+        let sound = match kind {
+            PickupKind::Gold(_) => audio_assets.gold.clone(),
+            _ => audio_assets.powerup.clone(),
+        };
+        audio.play(sound);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn despawn_missed_pickups_system(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform, &Collision), With<Pickup>>,
+    window_size: Res<WindowSize>,
+) {
+    for (entity, tf, collision) in query.iter() {
+        if tf.translation.y + collision.0.y / 2. < -window_size.height / 2. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
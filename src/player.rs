@@ -1,7 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use crate::prelude::*;
-use bevy::{prelude::*, sprite::collide_aabb::collide, time::FixedTimestep};
+use crate::projectiles::{resolve_impact, splash_reaches};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
 pub struct PlayerPlugin;
 
@@ -9,21 +10,58 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(PlayerState::default())
             .add_system_set(
-                SystemSet::new()
-                    .with_run_criteria(FixedTimestep::step(1.))
+                SystemSet::on_enter(GameState::Playing)
                     .with_system(spawn_player_system),
             )
-            .add_system(player_movement_system)
-            .add_system(player_input_system)
-            .add_system(spawn_player_laser_system)
-            .add_system(player_laser_hit_enemies)
-            .add_system(player_enemy_collision_system)
-            .add_system(handle_player_take_hit_event)
-            .add_system(handle_wave_complete_event_system)
-            .add_system(handle_player_death_event_system)
-            .add_event::<WaveCompleteEvent>()
-            .add_event::<PlayerLaserFireEvent>()
-            .add_event::<PlayerDeathEvent>();
+            .add_simulation_systems(
+                SimulationStep::Input,
+                SystemSet::new()
+                    .with_system(run_time_system)
+                    .with_system(player_input_system)
+                    .with_system(focus_system)
+                    .with_system(player_fire_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Fire,
+                SystemSet::new().with_system(spawn_player_laser_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Movement,
+                SystemSet::new().with_system(player_movement_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Hits,
+                // both send `EnemyTakeHitEvent`s, which roll drops in order
+                SystemSet::new()
+                    .with_system(player_laser_hit_enemies)
+                    .with_system(
+                        player_enemy_collision_system
+                            .after(player_laser_hit_enemies),
+                    ),
+            )
+            .add_simulation_systems(
+                SimulationStep::Damage,
+                SystemSet::new().with_system(handle_player_take_hit_event),
+            )
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new()
+                    .with_system(invulnerability_system)
+                    .with_system(handle_wave_complete_event_system)
+                    .with_system(handle_player_death_event_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(latch_fire_button_system)
+                    .with_system(charge_indicator_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(despawn_with::<Player>),
+            )
+            .add_simulation_event::<WaveCompleteEvent>()
+            .add_simulation_event::<PlayerLaserFireEvent>()
+            .add_simulation_event::<PlayerDeathEvent>();
     }
 }
 
@@ -31,86 +69,336 @@ fn spawn_player_system(
     mut commands: Commands,
     mut player_state: ResMut<PlayerState>,
     game_textures: Res<GameTextures>,
+    shape_assets: Res<ShapeAssets>,
     window_size: Res<WindowSize>,
 ) {
-    if player_state.is_alive {
-        return;
-    }
-
     let (px, py) = (0., -window_size.height * 1. / 4.);
-    commands.spawn((
-        SpriteBundle {
-            texture: game_textures.player.clone(),
-            transform: Transform {
-                translation: Vec3::new(px, py, 99.),
-                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: game_textures.player.clone(),
+                transform: Transform {
+                    translation: Vec3::new(px, py, 99.),
+                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        },
-        Player,
-        Collision::from(SIZE_PLAYER_SHIP),
-        Velocity::default(),
-    ));
+            Player,
+            SpawnIndex::PLAYER,
+            // only the core of the ship gets hit
+            Collider::circle(PLAYER_HURTBOX_RADIUS),
+            CollisionLayers::player(),
+            Velocity::default(),
+            Weapon::default(),
+            Dash::default(),
+            ShipHandling::default(),
+            Invulnerable::from_seconds(RESPAWN_INVULNERABILITY),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    texture: game_textures.laser_player.clone(),
+                    sprite: Sprite {
+                        color: CHARGE_SHOT_COLOR,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(
+                        0.,
+                        SIZE_PLAYER_SHIP.1 / 2.,
+                        1.,
+                    ),
+                    visibility: Visibility::INVISIBLE,
+                    ..default()
+                },
+                ChargeIndicator,
+            ));
+            parent.spawn((
+                MaterialMesh2dBundle {
+                    mesh: shape_assets.hitbox_marker.clone().into(),
+                    material: shape_assets.hitbox_material.clone(),
+                    transform: Transform::from_xyz(0., 0., 2.),
+                    visibility: Visibility::INVISIBLE,
+                    ..default()
+                },
+                HitboxMarker,
+            ));
+        });
 
     player_state.spawn();
 }
 
+/// Direction the player wants to go, at most 1 long. The keyboard wins over
+/// the left stick of any gamepad.
+fn move_input(
+    keyboard: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+) -> Vec2 {
+    let mut input = Vec2::ZERO;
+    if keyboard.pressed(KeyCode::S) || keyboard.pressed(KeyCode::Left) {
+        input.x -= 1.;
+    }
+    if keyboard.pressed(KeyCode::F) || keyboard.pressed(KeyCode::Right) {
+        input.x += 1.;
+    }
+    if keyboard.pressed(KeyCode::E) || keyboard.pressed(KeyCode::Up) {
+        input.y += 1.;
+    }
+    if keyboard.pressed(KeyCode::D) || keyboard.pressed(KeyCode::Down) {
+        input.y -= 1.;
+    }
+    if input != Vec2::ZERO {
+        // no faster diagonally
+        return input.normalize();
+    }
+
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.)
+        };
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if stick != Vec2::ZERO {
+            return stick.clamp_length_max(1.);
+        }
+    }
+    Vec2::ZERO
+}
+
+/// Steers `Velocity` towards the input instead of snapping to it, the ship
+/// speeds up with `acceleration` and coasts to a stop with `deceleration`.
 fn player_input_system(
     player_state: Res<PlayerState>,
     keyboard: Res<Input<KeyCode>>,
-    mut laser_fire_event: EventWriter<PlayerLaserFireEvent>,
-    mut query: Query<(&mut Velocity, &Transform), With<Player>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut query: Query<(&mut Velocity, &ShipHandling), With<Player>>,
+    time: Res<SimulationTime>,
 ) {
     if !player_state.is_alive {
         return;
     }
 
-    if let Ok((mut velocity, tf)) = query.get_single_mut() {
-        velocity.0.x = if keyboard.pressed(KeyCode::S)
-            || keyboard.pressed(KeyCode::Left)
-        {
-            -1.
-        } else if keyboard.pressed(KeyCode::F)
-            || keyboard.pressed(KeyCode::Right)
-        {
-            1.
+    if let Ok((mut velocity, handling)) = query.get_single_mut() {
+        let target = move_input(&keyboard, &gamepads, &axes);
+        let rate = if target == Vec2::ZERO {
+            handling.deceleration
+        } else {
+            handling.acceleration
+        };
+        let step = rate * time.delta_seconds();
+        let delta = target - velocity.0;
+        velocity.0 += if delta.length() <= step {
+            delta
         } else {
-            0.
+            delta.normalize() * step
         };
-        velocity.0.y =
-            if keyboard.pressed(KeyCode::E) || keyboard.pressed(KeyCode::Up) {
-                1.
-            } else if keyboard.pressed(KeyCode::D)
-                || keyboard.pressed(KeyCode::Down)
+    }
+}
+
+/// Left shift or a left trigger slows the ship down and reveals its hurtbox.
+fn focus_system(
+    mut commands: Commands,
+    player_state: Res<PlayerState>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    query: Query<(Entity, Option<&Focused>), With<Player>>,
+    mut query_marker: Query<&mut Visibility, With<HitboxMarker>>,
+) {
+    let (player, focused) = match query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let triggers: Vec<GamepadButton> = gamepads
+        .iter()
+        .flat_map(|gamepad| {
+            [
+                GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger),
+                GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger2),
+            ]
+        })
+        .collect();
+    let focus = player_state.is_alive
+        && (keyboard.pressed(KeyCode::LShift)
+            || buttons.any_pressed(triggers));
+
+    if focus && focused.is_none() {
+        commands.entity(player).insert(Focused);
+    } else if !focus && focused.is_some() {
+        commands.entity(player).remove::<Focused>();
+    }
+    for mut visibility in query_marker.iter_mut() {
+        visibility.is_visible = focus;
+    }
+}
+
+/// Fire button state as `(held, just_released)`, Space or a right trigger.
+/// The release only lasts a frame, the simulation gets it from `InputLatch`.
+fn fire_button(
+    keyboard: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+) -> (bool, bool) {
+    let triggers: Vec<GamepadButton> = gamepads
+        .iter()
+        .flat_map(|gamepad| {
+            [
+                GamepadButton::new(gamepad, GamepadButtonType::RightTrigger),
+                GamepadButton::new(gamepad, GamepadButtonType::RightTrigger2),
+            ]
+        })
+        .collect();
+    let held = keyboard.pressed(KeyCode::Space)
+        || buttons.any_pressed(triggers.iter().copied());
+    let released = keyboard.just_released(KeyCode::Space)
+        || buttons.any_just_released(triggers);
+    (held, released)
+}
+
+fn latch_fire_button_system(
+    mut input_latch: ResMut<InputLatch>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let (_, released) = fire_button(&keyboard, &gamepads, &buttons);
+    input_latch.fire_released |= released;
+}
+
+fn player_fire_system(
+    player_state: Res<PlayerState>,
+    mut input_latch: ResMut<InputLatch>,
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut laser_fire_event: EventWriter<PlayerLaserFireEvent>,
+    mut query: Query<
+        (&Transform, &mut Weapon, Option<&Focused>),
+        With<Player>,
+    >,
+    time: Res<SimulationTime>,
+) {
+    let released = std::mem::take(&mut input_latch.fire_released);
+    if !player_state.is_alive {
+        return;
+    }
+    let (tf, mut weapon, focused) = match query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let (held, _) = fire_button(&keyboard, &gamepads, &buttons);
+    let position = Vec2::new(
+        tf.translation.x,
+        tf.translation.y + SIZE_PLAYER_SHIP.1 / 2.,
+    );
+    let level = weapon.level;
+    let mut fire = |payload: ShotPayload| {
+        laser_fire_event.send(PlayerLaserFireEvent {
+            position,
+            weapon: level,
+            payload,
+            focused: focused.is_some(),
+        })
+    };
+
+    // every weapon charges while held, letting go fires the charge shot
+    let charge = weapon.charge.min(MAX_CHARGE_TIME);
+    if held {
+        weapon.charge += time.delta_seconds();
+    } else if released {
+        weapon.charge = 0.;
+    }
+    let charged = released && charge >= CHARGE_THRESHOLD;
+    if charged {
+        fire(ShotPayload::Charged { charge });
+    }
+
+    weapon.cooldown.tick(time.delta());
+    match weapon.level.definition().fire_mode {
+        FireMode::Auto => {
+            if held && weapon.cooldown.finished() {
+                fire(ShotPayload::Volley);
+                weapon.cooldown.reset();
+            }
+        }
+        FireMode::TapBurst { shots, .. } => {
+            if weapon.burst_left > 0
+                && weapon.burst_timer.tick(time.delta()).just_finished()
             {
-                -1.
-            } else {
-                0.
-            };
-        if keyboard.just_pressed(KeyCode::Space) {
-            let offset = Vec2::new(
-                tf.translation.x,
-                tf.translation.y + SIZE_PLAYER_SHIP.1 / 2.,
-            );
-            laser_fire_event.send(PlayerLaserFireEvent(offset));
+                weapon.burst_left -= 1;
+                fire(ShotPayload::Volley);
+            }
+            if released && !charged && weapon.cooldown.finished() {
+                fire(ShotPayload::Volley);
+                weapon.burst_left = shots.saturating_sub(1);
+                weapon.burst_timer.reset();
+                weapon.cooldown.reset();
+            }
         }
     }
 }
 
+fn charge_indicator_system(
+    query_weapon: Query<&Weapon, With<Player>>,
+    mut query: Query<(&mut Transform, &mut Visibility), With<ChargeIndicator>>,
+) {
+    let charge = match query_weapon.get_single() {
+        Ok(weapon) => weapon.charge.min(MAX_CHARGE_TIME),
+        Err(_) => return,
+    };
+    for (mut tf, mut visibility) in query.iter_mut() {
+        visibility.is_visible = charge >= CHARGE_THRESHOLD;
+        let scale = charge / MAX_CHARGE_TIME;
+        tf.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+fn run_time_system(
+    mut player_state: ResMut<PlayerState>,
+    time: Res<SimulationTime>,
+) {
+    player_state.run_time.tick(time.delta());
+}
+
 fn player_movement_system(
     window_size: Res<WindowSize>,
-    mut query: Query<(&mut Transform, &Velocity), With<Player>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &Velocity,
+            &ShipHandling,
+            Option<&Dash>,
+            Option<&Focused>,
+        ),
+        With<Player>,
+    >,
     player_state: Res<PlayerState>,
-    time: Res<Time>,
+    time: Res<SimulationTime>,
 ) {
-    for (mut tf, velocity) in query.iter_mut() {
+    for (mut tf, velocity, handling, dash, focused) in query.iter_mut() {
+        // bank into turns
+        tf.rotation = Quat::from_rotation_z(-velocity.0.x * handling.max_bank);
+
+        // a dash takes over until it's done
+        let velocity = match dash {
+            Some(dash) if dash.is_active() => {
+                dash.direction * DASH_SPEED_MULTIPLIER
+            }
+            _ if focused.is_some() => velocity.0 * FOCUS_SPEED_SCALE,
+            _ => velocity.0,
+        };
+
         // handle vertical movement
         //let new_y =
         //tf.translation.y + velocity.0.y * time.delta_seconds() * BASE_SPEED;
 
         let new_y = tf.translation.y
-            + velocity.0.y * time.delta_seconds() * player_state.speed;
+            + velocity.y * time.delta_seconds() * player_state.speed;
         tf.translation.y = f32::clamp(
             new_y,
             -window_size.height / 2. + SIZE_PLAYER_SHIP.1 / 2. * SPRITE_SCALE,
@@ -120,7 +408,7 @@ fn player_movement_system(
         // handle horizontal movement
         //tf.translation.x += velocity.0.x * BASE_SPEED * time.delta_seconds();
         tf.translation.x +=
-            velocity.0.x * player_state.speed * time.delta_seconds();
+            velocity.x * player_state.speed * time.delta_seconds();
         if tf.translation.x - SIZE_PLAYER_SHIP.0 * SPRITE_SCALE
             >= window_size.width / 2.
         {
@@ -134,138 +422,379 @@ fn player_movement_system(
     }
 }
 
-fn spawn_player_laser_system(
+pub fn spawn_player_laser_system(
     mut commands: Commands,
     mut player_laser_fire_events: EventReader<PlayerLaserFireEvent>,
+    mut spawn_counter: ResMut<SpawnCounter>,
     audio_assets: Res<AudioAssets>,
     game_textures: Res<GameTextures>,
     audio: Res<Audio>,
 ) {
     for event in player_laser_fire_events.iter() {
-        audio.play(audio_assets.player_shoot.clone());
-        let (laser_x, laser_y) = (
-            event.0.x,
-            event.0.y + (SIZE_PLAYER_SHIP.1 / 2. * SPRITE_SCALE) + 1.,
+        let nose = Vec2::new(
+            event.position.x,
+            event.position.y + SIZE_PLAYER_SHIP.1 / 2. * SPRITE_SCALE + 1.,
         );
-        commands.spawn((
+        match event.payload {
+            ShotPayload::Volley => {
+                let spread = if event.focused {
+                    FOCUS_SPREAD_SCALE
+                } else {
+                    1.
+                };
+                audio.play(audio_assets.player_shoot.clone());
+                spawn_volley(
+                    &mut commands,
+                    &mut spawn_counter,
+                    &game_textures,
+                    event.weapon.definition(),
+                    nose,
+                    spread,
+                );
+            }
+            ShotPayload::Charged { charge } => {
+                audio.play_with_settings(
+                    audio_assets.player_shoot.clone(),
+                    PlaybackSettings::ONCE.with_speed(0.6),
+                );
+                spawn_charge_shot(
+                    &mut commands,
+                    &mut spawn_counter,
+                    &game_textures,
+                    charge,
+                    nose,
+                );
+            }
+        }
+    }
+}
+
+fn spawn_volley(
+    commands: &mut Commands,
+    spawn_counter: &mut SpawnCounter,
+    game_textures: &GameTextures,
+    definition: &WeaponDefinition,
+    nose: Vec2,
+    spread: f32,
+) {
+    for shot in definition.shots.iter() {
+        let (offset, angle) = (shot.offset * spread, shot.angle * spread);
+        let size = Vec2::new(shot.kind.size().0, shot.kind.size().1);
+        let direction = Vec2::new(angle.sin(), angle.cos());
+        let mut laser = commands.spawn((
             SpriteBundle {
-                texture: game_textures.laser_player.clone(),
+                texture: game_textures.player_shot(shot.kind),
                 transform: Transform {
-                    translation: Vec3::new(laser_x, laser_y, 1.),
+                    translation: Vec3::new(nose.x + offset, nose.y, 1.),
+                    rotation: Quat::from_rotation_z(-angle),
                     scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..default()
                 },
                 ..default()
             },
             Laser,
             FromPlayer,
+            spawn_counter.next(),
             Movable { auto_despawn: true },
-            Collision::from(SIZE_LASER_PLAYER),
-            Velocity(Vec2::new(0., 1.5)),
+            Collision::from(shot.kind.size()),
+            Collider::fit(size),
+            CollisionLayers::player_laser(),
+            Swept::default(),
+            Velocity(direction * shot.speed),
+            Damage(definition.damage),
         ));
+        shot.behaviour.insert(&mut laser);
     }
 }
 
+/// Damage and size grow with `charge`, and the laser goes through everything
+/// in its way.
+fn spawn_charge_shot(
+    commands: &mut Commands,
+    spawn_counter: &mut SpawnCounter,
+    game_textures: &GameTextures,
+    charge: f32,
+    nose: Vec2,
+) {
+    let damage = (CHARGE_SHOT_DAMAGE as f32
+        * (1. + charge * CHARGE_DAMAGE_SCALE))
+        .round() as i32;
+    let scale = SPRITE_SCALE * (1. + charge * CHARGE_SIZE_SCALE);
+    let size = Vec2::new(SIZE_LASER_PLAYER.0, SIZE_LASER_PLAYER.1) * scale;
+    commands.spawn((
+        SpriteBundle {
+            texture: game_textures.laser_player.clone(),
+            sprite: Sprite {
+                color: CHARGE_SHOT_COLOR,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(nose.x, nose.y + size.y / 2., 1.),
+                scale: Vec3::new(scale, scale, 1.),
+                ..default()
+            },
+            ..default()
+        },
+        Laser,
+        FromPlayer,
+        spawn_counter.next(),
+        Movable { auto_despawn: true },
+        Collision(size),
+        Collider::fit(size),
+        CollisionLayers::player_laser(),
+        Swept::default(),
+        Velocity(Vec2::new(0., 2.)),
+        Damage(damage),
+        Pierce {
+            hits_left: u32::MAX,
+            hit: Vec::new(),
+        },
+    ));
+}
+
 fn player_laser_hit_enemies(
     mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
     mut hit_enemy_event: EventWriter<EnemyTakeHitEvent>,
-    query_player_laser: Query<
-        (Entity, &Transform, &Collision),
+    mut explosion_event: EventWriter<ExplosionEvent>,
+    mut query_player_laser: Query<
+        (&SpawnIndex, &Damage, Option<&mut Pierce>, Option<&Splash>),
         (With<Laser>, With<FromPlayer>),
     >,
-    query_enemies: Query<(Entity, &Transform, &Collision), With<Enemy>>,
+    query_enemies: Query<
+        (Entity, &SpawnIndex, &Transform, &Collider),
+        With<Enemy>,
+    >,
+    query_weak_points: Query<(&GlobalTransform, &WeakPoint, &Parent)>,
 ) {
-    let mut despawned: HashSet<Entity> = HashSet::new();
-    for (pl_entity, pl_tf, pl_size) in query_player_laser.iter() {
-        if despawned.contains(&pl_entity) {
-            continue;
+    // everything each laser touched this tick, lasers in spawn order
+    let mut touched: BTreeMap<SpawnIndex, (Entity, Vec<Entity>)> =
+        BTreeMap::new();
+    for event in collision_events.iter() {
+        let (laser, target) =
+            match event.either(|entity| query_player_laser.contains(entity)) {
+                Some(contact) => contact,
+                None => continue,
+            };
+        if let Ok((index, ..)) = query_player_laser.get(laser) {
+            touched
+                .entry(*index)
+                .or_insert_with(|| (laser, Vec::new()))
+                .1
+                .push(target);
         }
-        for (e_entity, e_tf, e_size) in query_enemies.iter() {
-            if despawned.contains(&e_entity) || despawned.contains(&pl_entity) {
-                continue;
-            }
+    }
 
-            let collision = collide(
-                pl_tf.translation,
-                pl_size.0,
-                e_tf.translation,
-                e_size.0,
-            );
-            if collision.is_some() {
-                commands.entity(pl_entity).despawn_recursive();
-                despawned.insert(pl_entity);
-                despawned.insert(e_entity);
-                hit_enemy_event
-                    .send(EnemyTakeHitEvent(e_entity, e_tf.translation));
+    'lasers: for (laser, targets) in touched.into_values() {
+        let (_, damage, mut pierce, splash) =
+            match query_player_laser.get_mut(laser) {
+                Ok(laser) => laser,
+                Err(_) => continue,
+            };
+        let mut hits: Vec<(bool, EnemyTakeHitEvent)> = targets
+            .iter()
+            .filter_map(|target| {
+                if let Ok((wp_tf, weak_point, boss)) =
+                    query_weak_points.get(*target)
+                {
+                    return Some((
+                        true,
+                        EnemyTakeHitEvent {
+                            entity: boss.get(),
+                            position: wp_tf.translation(),
+                            damage: damage.0 * weak_point.multiplier,
+                        },
+                    ));
+                }
+                let (entity, _, tf, _) = query_enemies.get(*target).ok()?;
+                Some((
+                    false,
+                    EnemyTakeHitEvent {
+                        entity,
+                        position: tf.translation,
+                        damage: damage.0,
+                    },
+                ))
+            })
+            .collect();
+        // weak points sit on top of their boss, so they get the first say
+        hits.sort_by_key(|(weak_point, _)| !weak_point);
+
+        // the first hit on a target counts, its weak point if touched
+        for (_, hit) in hits {
+            let spent = match resolve_impact(pierce.as_deref_mut(), hit.entity)
+            {
+                Some(spent) => spent,
+                None => continue,
+            };
+
+            if let Some(splash) = splash {
+                explosion_event.send(ExplosionEvent {
+                    position: hit.position.truncate(),
+                    with_sound: false,
+                });
+                let mut splashed: Vec<_> = query_enemies
+                    .iter()
+                    .filter(|(e_entity, _, e_tf, collider)| {
+                        *e_entity != hit.entity
+                            && splash_reaches(
+                                splash,
+                                hit.position,
+                                collider,
+                                e_tf,
+                            )
+                    })
+                    .collect();
+                splashed.sort_by_key(|(_, index, ..)| **index);
+                for (e_entity, _, e_tf, _) in splashed {
+                    hit_enemy_event.send(EnemyTakeHitEvent {
+                        entity: e_entity,
+                        position: e_tf.translation,
+                        damage: damage.0,
+                    });
+                }
+            }
+            hit_enemy_event.send(hit);
+            // a laser is spent on the first enemy it touches, unless it
+            // pierces
+            if spent {
+                commands.entity(laser).despawn_recursive();
+                continue 'lasers;
             }
         }
     }
 }
 
 fn player_enemy_collision_system(
+    mut collision_events: EventReader<CollisionEvent>,
     mut player_take_hit_event: EventWriter<PlayerTakeHitEvent>,
     mut enemy_take_hit_event: EventWriter<EnemyTakeHitEvent>,
-    query_enemies: Query<(Entity, &Transform, &Collision), With<Enemy>>,
-    query_player: Query<(&Transform, &Collision), With<Player>>,
+    query_enemies: Query<(&Transform, &Health, Option<&Boss>), With<Enemy>>,
+    // it can't be hurt and neither can what it touches, or it would grind
+    // through them every tick
+    query_player: Query<(), (With<Player>, Without<Invulnerable>)>,
 ) {
-    let player = query_player.get_single();
-    if let Ok((player_tf, player_size)) = player {
-        for (enemy_entity, enemy_tf, enemy_size) in query_enemies.iter() {
-            let collision = collide(
-                player_tf.translation,
-                player_size.0,
-                enemy_tf.translation,
-                enemy_size.0,
-            );
-            if collision.is_some() {
-                player_take_hit_event.send_default();
-                enemy_take_hit_event.send(EnemyTakeHitEvent(
-                    enemy_entity,
-                    enemy_tf.translation,
-                ));
-            }
-        }
+    for event in collision_events.iter() {
+        let enemy = match event.either(|entity| query_player.contains(entity))
+        {
+            Some((_, enemy)) => enemy,
+            None => continue,
+        };
+        let (enemy_tf, health, boss) = match query_enemies.get(enemy) {
+            Ok(enemy) => enemy,
+            Err(_) => continue,
+        };
+        player_take_hit_event.send_default();
+        // ramming destroys anything short of a boss
+        let damage = match boss {
+            Some(_) => BOSS_RAM_DAMAGE,
+            None => health.0,
+        };
+        enemy_take_hit_event.send(EnemyTakeHitEvent {
+            entity: enemy,
+            position: enemy_tf.translation,
+            damage,
+        });
     }
 }
 
 fn handle_player_take_hit_event(
     mut commands: Commands,
     mut player_state: ResMut<PlayerState>,
+    mut shield: ResMut<Shield>,
     mut enemy_attrs: ResMut<EnemyAttributes>,
-    mut take_hit_events: EventReader<PlayerTakeHitEvent>,
+    take_hit_events: EventReader<PlayerTakeHitEvent>,
     mut explosion_event: EventWriter<ExplosionEvent>,
     mut player_death_event: EventWriter<PlayerDeathEvent>,
-    mut query: Query<(Entity, &mut Transform), With<Player>>,
+    mut query: Query<
+        (Entity, &mut Transform, &mut Weapon, Option<&Invulnerable>),
+        With<Player>,
+    >,
     window_size: Res<WindowSize>,
     audio_assets: Res<AudioAssets>,
     audio: Res<Audio>,
 ) {
-    for _ in take_hit_events.iter() {
+    // everything that hits in the same tick counts as a single hit
+    if take_hit_events.is_empty() {
+        return;
+    }
+    take_hit_events.clear();
+    if let Ok((_, _, _, Some(_))) = query.get_single() {
+        return;
+    }
+
+    let absorbed = shield.absorb();
+    if absorbed {
+        if shield.charges > 0 {
+            // higher pitched while it holds
+            audio.play_with_settings(
+                audio_assets.hit.clone(),
+                PlaybackSettings::ONCE.with_speed(1.5),
+            );
+        } else {
+            // and low and loud once it breaks
+            audio.play_with_settings(
+                audio_assets.hit.clone(),
+                PlaybackSettings::ONCE.with_speed(0.5).with_volume(1.5),
+            );
+        }
+    } else {
         player_state.decrement_health();
+    }
 
-        if player_state.is_alive {
+    if player_state.is_alive {
+        if !absorbed {
             audio.play(audio_assets.hit.clone());
-        } else {
-            if !player_state.death_sound_played {
-                audio.play(audio_assets.death.clone());
-                player_state.death_sound_played = true;
-                if let Ok((entity, mut tf)) = query.get_single_mut() {
-                    explosion_event.send(ExplosionEvent {
-                        position: Vec2::new(tf.translation.x, tf.translation.y),
-                        with_sound: true,
-                    });
+        }
+        if let Ok((entity, _, mut weapon, _)) = query.get_single_mut() {
+            // losing a life costs a weapon level, the shield saves it
+            if !absorbed {
+                weapon.downgrade();
+            }
+            commands
+                .entity(entity)
+                .insert(Invulnerable::from_seconds(HIT_INVULNERABILITY));
+        }
+    } else {
+        if !player_state.death_sound_played {
+            audio.play(audio_assets.death.clone());
+            player_state.death_sound_played = true;
+            if let Ok((entity, mut tf, _, _)) = query.get_single_mut() {
+                explosion_event.send(ExplosionEvent {
+                    position: Vec2::new(tf.translation.x, tf.translation.y),
+                    with_sound: true,
+                });
 
-                    let (px, py) = (0., -window_size.height * 1. / 4.);
-                    tf.translation = Vec3::new(px, py, 99.);
-                    commands.entity(entity).despawn_recursive();
-                    player_state.is_alive = false;
-                    enemy_attrs.reset();
-                    player_death_event.send_default();
-                }
+                let (px, py) = (0., -window_size.height * 1. / 4.);
+                tf.translation = Vec3::new(px, py, 99.);
+                commands.entity(entity).despawn_recursive();
+                player_state.is_alive = false;
+                enemy_attrs.reset();
+                player_death_event.send_default();
             }
         }
     }
 }
+
+fn invulnerability_system(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &mut Invulnerable, &mut Visibility),
+        With<Player>,
+    >,
+    time: Res<SimulationTime>,
+) {
+    for (entity, mut invulnerable, mut visibility) in query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            visibility.is_visible = true;
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+        let elapsed = invulnerable.0.elapsed_secs();
+        let blinks = (elapsed / INVULNERABLE_BLINK_INTERVAL) as u32;
+        visibility.is_visible = blinks % 2 == 1;
+    }
+}
+
 fn handle_wave_complete_event_system(
     mut events: EventReader<WaveCompleteEvent>,
     mut enemy_attrs: ResMut<EnemyAttributes>,
@@ -276,12 +805,14 @@ fn handle_wave_complete_event_system(
     for _ in events.iter() {
         enemy_attrs.upgrade();
         player_state.upgrade();
+        player_state.increment_waves();
         audio.play(audio_assets.powerup.clone());
     }
 }
 
 fn handle_player_death_event_system(
     mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
     mut events: EventReader<PlayerDeathEvent>,
     game_textures: Res<GameTextures>,
     query_lasers: Query<Entity, With<Laser>>,
@@ -318,5 +849,7 @@ fn handle_player_death_event_system(
         for entity in entities {
             commands.entity(entity).despawn_recursive();
         }
+
+        state.set(GameState::GameOver).unwrap();
     }
 }
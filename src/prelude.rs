@@ -1,5 +1,10 @@
+pub use crate::collision::*;
 pub use crate::components::*;
 pub use crate::constants::*;
+pub use crate::enemy_kinds::*;
 pub use crate::resources::*;
 pub use crate::shared::*;
+pub use crate::simulation::*;
 pub use crate::events::*;
+pub use crate::states::*;
+pub use crate::weapons::*;
@@ -0,0 +1,251 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_systems(
+            SimulationStep::Steering,
+            SystemSet::new()
+                .with_system(homing_system)
+                .with_system(bounce_system),
+        );
+    }
+}
+
+/// Optional behaviours of a projectile, zero means "doesn't have it". Shared
+/// by player weapons and enemy kinds, see `insert`.
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectileBehaviour {
+    /// extra targets it goes through after the first one
+    pub pierce: u32,
+    /// radius in pixels of the area damaged on impact
+    pub splash: f32,
+    /// radians per second it turns towards its target
+    pub homing: f32,
+    /// times it bounces off the edges of the screen
+    pub bounces: u32,
+}
+
+impl ProjectileBehaviour {
+    pub const NONE: Self = Self {
+        pierce: 0,
+        splash: 0.,
+        homing: 0.,
+        bounces: 0,
+    };
+
+    /// Adds the components of every behaviour set on a fresh projectile.
+    pub fn insert(&self, projectile: &mut EntityCommands) {
+        if self.pierce > 0 {
+            projectile.insert(Pierce {
+                hits_left: self.pierce + 1,
+                hit: Vec::new(),
+            });
+        }
+        if self.splash > 0. {
+            projectile.insert(Splash {
+                radius: self.splash,
+            });
+        }
+        if self.homing > 0. {
+            projectile.insert(Homing {
+                turn_rate: self.homing,
+            });
+        }
+        if self.bounces > 0 {
+            projectile.insert(Bounce {
+                bounces_left: self.bounces,
+            });
+        }
+    }
+}
+
+/// Whether a projectile touching `target` hits it, and if so whether the
+/// hit uses the projectile up. Piercing projectiles hit every target once.
+pub fn resolve_impact(
+    pierce: Option<&mut Pierce>,
+    target: Entity,
+) -> Option<bool> {
+    let pierce = match pierce {
+        Some(pierce) => pierce,
+        None => return Some(true),
+    };
+    if pierce.hit.contains(&target) {
+        return None;
+    }
+    pierce.hit.push(target);
+    pierce.hits_left = pierce.hits_left.saturating_sub(1);
+    Some(pierce.hits_left == 0)
+}
+
+/// Whether a splash going off at `position` reaches a target. Anything the
+/// blast touches counts, not just what it's centered on.
+pub fn splash_reaches(
+    splash: &Splash,
+    position: Vec3,
+    collider: &Collider,
+    tf: &Transform,
+) -> bool {
+    let blast = Collider::circle(splash.radius);
+    blast.intersects(&Transform::from_translation(position), collider, tf)
+}
+
+/// Turns a projectile sprite to face where it's going. Player shots are
+/// drawn pointing up, enemy shots pointing down.
+fn face_velocity(tf: &mut Transform, velocity: Vec2, from_player: bool) {
+    let heading = velocity.y.atan2(velocity.x);
+    let rotation = if from_player {
+        heading - FRAC_PI_2
+    } else {
+        heading + FRAC_PI_2
+    };
+    tf.rotation = Quat::from_rotation_z(rotation);
+}
+
+/// Player projectiles chase the closest enemy, enemy ones the player.
+fn homing_system(
+    mut query: Query<
+        (&mut Transform, &mut Velocity, &Homing, Option<&FromPlayer>),
+        (With<Laser>, Without<Enemy>, Without<Player>),
+    >,
+    query_enemies: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    query_player: Query<&Transform, With<Player>>,
+    time: Res<SimulationTime>,
+) {
+    let player = query_player
+        .get_single()
+        .ok()
+        .map(|player_tf| player_tf.translation.truncate());
+    for (mut tf, mut velocity, homing, from_player) in query.iter_mut() {
+        let position = tf.translation.truncate();
+        let target = match from_player {
+            Some(_) => query_enemies
+                .iter()
+                .map(|enemy_tf| enemy_tf.translation.truncate())
+                .min_by(|a, b| {
+                    a.distance_squared(position)
+                        .total_cmp(&b.distance_squared(position))
+                }),
+            None => player,
+        };
+        let target = match target {
+            Some(target) => target,
+            None => continue,
+        };
+
+        // turn towards the target, never faster than the turn rate
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let to_target = target - position;
+        let turn = velocity
+            .0
+            .perp_dot(to_target)
+            .atan2(velocity.0.dot(to_target));
+        let heading =
+            velocity.0.y.atan2(velocity.0.x) + turn.clamp(-max_turn, max_turn);
+        let speed = velocity.0.length();
+        velocity.0 = Vec2::new(heading.cos(), heading.sin()) * speed;
+        face_velocity(&mut tf, velocity.0, from_player.is_some());
+    }
+}
+
+fn bounce_system(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Velocity,
+        &Collision,
+        &mut Bounce,
+        Option<&FromPlayer>,
+    )>,
+    window_size: Res<WindowSize>,
+) {
+    for (entity, mut tf, mut velocity, collision, mut bounce, from_player) in
+        query.iter_mut()
+    {
+        // keep a pixel away from the edge, `auto_despawner_system` takes
+        // over once the last bounce is spent
+        let limit = Vec2::new(window_size.width, window_size.height) / 2.
+            - collision.0 / 2.
+            - 1.;
+        let mut bounced = false;
+        if tf.translation.x.abs() > limit.x
+            && tf.translation.x * velocity.0.x > 0.
+        {
+            velocity.0.x = -velocity.0.x;
+            tf.translation.x = tf.translation.x.clamp(-limit.x, limit.x);
+            bounced = true;
+        }
+        if tf.translation.y.abs() > limit.y
+            && tf.translation.y * velocity.0.y > 0.
+        {
+            velocity.0.y = -velocity.0.y;
+            tf.translation.y = tf.translation.y.clamp(-limit.y, limit.y);
+            bounced = true;
+        }
+        if !bounced {
+            continue;
+        }
+
+        face_velocity(&mut tf, velocity.0, from_player.is_some());
+        bounce.bounces_left = bounce.bounces_left.saturating_sub(1);
+        if bounce.bounces_left == 0 {
+            commands.entity(entity).remove::<Bounce>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pierce(hits_left: u32) -> Pierce {
+        Pierce {
+            hits_left,
+            hit: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn plain_projectiles_are_spent_on_the_first_hit() {
+        assert_eq!(resolve_impact(None, Entity::from_raw(1)), Some(true));
+    }
+
+    #[test]
+    fn pierce_counts_down_and_is_spent_on_the_last_hit() {
+        let mut pierce = pierce(3);
+        let targets = [1, 2, 3].map(Entity::from_raw);
+        assert_eq!(resolve_impact(Some(&mut pierce), targets[0]), Some(false));
+        assert_eq!(pierce.hits_left, 2);
+        assert_eq!(resolve_impact(Some(&mut pierce), targets[1]), Some(false));
+        assert_eq!(pierce.hits_left, 1);
+        assert_eq!(resolve_impact(Some(&mut pierce), targets[2]), Some(true));
+        assert_eq!(pierce.hits_left, 0);
+    }
+
+    #[test]
+    fn pierce_hits_every_target_once() {
+        let mut pierce = pierce(3);
+        let target = Entity::from_raw(1);
+        assert_eq!(resolve_impact(Some(&mut pierce), target), Some(false));
+        // still touching it on the next tick
+        assert_eq!(resolve_impact(Some(&mut pierce), target), None);
+        assert_eq!(pierce.hits_left, 2);
+        assert_eq!(pierce.hit, vec![target]);
+    }
+
+    #[test]
+    fn splash_reaches_whatever_the_blast_touches() {
+        let splash = Splash { radius: 10. };
+        let target = Collider::circle(4.);
+        let at = |x: f32| Transform::from_xyz(x, 0., 0.);
+        assert!(splash_reaches(&splash, Vec3::ZERO, &target, &at(0.)));
+        // the edges touching is enough
+        assert!(splash_reaches(&splash, Vec3::ZERO, &target, &at(14.)));
+        assert!(!splash_reaches(&splash, Vec3::ZERO, &target, &at(14.5)));
+    }
+}
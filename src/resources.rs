@@ -1,6 +1,14 @@
-use bevy::prelude::*;
+use std::time::Duration;
 
-use crate::prelude::{BASE_SPEED, ENEMY_BASE_FIRE_RATE, ENEMY_BASE_VELOCITY};
+use bevy::{audio::AudioSink, prelude::*, time::Stopwatch, utils::HashMap};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::prelude::{
+    EnemyKind, PickupKind, ShotKind, SpawnIndex, BASE_SPEED,
+    ENEMY_BASE_VELOCITY, MAX_BOMBS, MAX_HEALTH, MAX_SHIELD_CAPACITY,
+    MAX_SIMULATION_LAG, PLAYER_START_BOMBS, SHIELD_BASE_CAPACITY,
+    SHIELD_REGEN_DELAY, SHIELD_REGEN_INTERVAL, SIMULATION_STEP,
+};
 
 #[derive(Resource)]
 pub struct BackgroundSpeed(pub f32);
@@ -10,6 +18,10 @@ pub struct PlayerState {
     pub health: i32,
     pub golds: i32,
     pub score: i32,
+    pub kills: i32,
+    pub waves: i32,
+    pub run_time: Stopwatch,
+    pub bombs: i32,
     pub is_alive: bool,
     pub death_sound_played: bool,
     pub speed: f32,
@@ -17,7 +29,7 @@ pub struct PlayerState {
 
 impl PlayerState {
     pub fn increment_health(&mut self) {
-        if self.is_alive && self.health < 3 {
+        if self.is_alive && self.health < MAX_HEALTH {
             self.health += 1;
         }
     }
@@ -52,8 +64,24 @@ impl PlayerState {
         self.golds += 1;
     }
 
-    pub fn increment_score(&mut self) {
-        self.score += 1;
+    pub fn add_gold(&mut self, amount: i32) {
+        self.golds += amount;
+    }
+
+    pub fn add_score(&mut self, points: i32) {
+        self.score += points;
+    }
+
+    pub fn increment_kills(&mut self) {
+        self.kills += 1;
+    }
+
+    pub fn increment_waves(&mut self) {
+        self.waves += 1;
+    }
+
+    pub fn add_bomb(&mut self) {
+        self.bombs = MAX_BOMBS.min(self.bombs + 1);
     }
 }
 
@@ -64,18 +92,77 @@ impl Default for PlayerState {
             health: 3,
             golds: 0,
             score: 0,
+            kills: 0,
+            waves: 0,
+            run_time: Stopwatch::new(),
+            bombs: PLAYER_START_BOMBS,
             death_sound_played: false,
             speed: BASE_SPEED,
         }
     }
 }
 
+/// Hits absorbed before health is touched. Spent charges come back one by
+/// one once the player goes `SHIELD_REGEN_DELAY` seconds without a hit.
+#[derive(Resource)]
+pub struct Shield {
+    pub capacity: i32,
+    pub charges: i32,
+    pub since_hit: f32,
+    pub regen: Timer,
+}
+
+impl Shield {
+    /// Takes a hit, returns whether the shield soaked it up.
+    pub fn absorb(&mut self) -> bool {
+        self.since_hit = 0.;
+        self.regen.reset();
+        if self.charges > 0 {
+            self.charges -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn regenerate(&mut self, delta: Duration) {
+        if self.charges >= self.capacity {
+            return;
+        }
+        self.since_hit += delta.as_secs_f32();
+        if self.since_hit >= SHIELD_REGEN_DELAY
+            && self.regen.tick(delta).just_finished()
+        {
+            self.charges += 1;
+        }
+    }
+
+    /// One more charge of capacity, and a full shield.
+    pub fn upgrade(&mut self) {
+        self.capacity = MAX_SHIELD_CAPACITY.min(self.capacity + 1);
+        self.charges = self.capacity;
+    }
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            capacity: SHIELD_BASE_CAPACITY,
+            charges: SHIELD_BASE_CAPACITY,
+            since_hit: 0.,
+            regen: Timer::from_seconds(
+                SHIELD_REGEN_INTERVAL,
+                TimerMode::Repeating,
+            ),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct EnemyCount(pub i32);
 
 #[derive(Resource)]
 pub struct EnemyAttributes {
-    pub fire_rate: f64,
     pub velocity: Vec2,
 }
 
@@ -91,7 +178,6 @@ impl EnemyAttributes {
 impl Default for EnemyAttributes {
     fn default() -> Self {
         Self {
-            fire_rate: ENEMY_BASE_FIRE_RATE,
             velocity: ENEMY_BASE_VELOCITY,
         }
     }
@@ -101,10 +187,37 @@ impl Default for EnemyAttributes {
 pub struct GameTextures {
     pub player: Handle<Image>,
     pub laser_player: Handle<Image>,
-    pub laser_enemy: Handle<Image>,
-    pub enemy: Handle<Image>,
+    pub beam_player: Handle<Image>,
+    pub missile_player: Handle<Image>,
+    pub enemies: HashMap<EnemyKind, Handle<Image>>,
+    pub enemy_lasers: HashMap<EnemyKind, Handle<Image>>,
     pub background: Handle<Image>,
     pub explosion: Handle<TextureAtlas>,
+    pub pickup_gold: Handle<Image>,
+    pub pickup_health: Handle<Image>,
+    pub pickup_weapon: Handle<Image>,
+    pub pickup_shield: Handle<Image>,
+    pub pickup_bomb: Handle<Image>,
+}
+
+impl GameTextures {
+    pub fn player_shot(&self, kind: ShotKind) -> Handle<Image> {
+        match kind {
+            ShotKind::Laser => self.laser_player.clone(),
+            ShotKind::Beam => self.beam_player.clone(),
+            ShotKind::Missile => self.missile_player.clone(),
+        }
+    }
+
+    pub fn pickup(&self, kind: PickupKind) -> Handle<Image> {
+        match kind {
+            PickupKind::Gold(_) => self.pickup_gold.clone(),
+            PickupKind::Health => self.pickup_health.clone(),
+            PickupKind::WeaponUpgrade => self.pickup_weapon.clone(),
+            PickupKind::Shield => self.pickup_shield.clone(),
+            PickupKind::Bomb => self.pickup_bomb.clone(),
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -112,6 +225,15 @@ pub struct FontAssets {
     pub ui: Handle<Font>,
 }
 
+/// Shapes drawn over the player, made once instead of on every spawn.
+#[derive(Resource)]
+pub struct ShapeAssets {
+    pub shield_bubble: Handle<Mesh>,
+    pub shield_material: Handle<ColorMaterial>,
+    pub hitbox_marker: Handle<Mesh>,
+    pub hitbox_material: Handle<ColorMaterial>,
+}
+
 #[derive(Resource)]
 pub struct AudioAssets {
     pub player_shoot: Handle<AudioSource>,
@@ -124,6 +246,10 @@ pub struct AudioAssets {
     pub theme_song: Handle<AudioSource>,
 }
 
+/// Sink of the looping theme song, kept around so it can be paused.
+#[derive(Resource)]
+pub struct MusicController(pub Handle<AudioSink>);
+
 #[derive(Resource)]
 pub struct WindowSize {
     pub width: f32,
@@ -135,3 +261,214 @@ impl WindowSize {
         Self { width, height }
     }
 }
+
+/// Clock of the fixed simulation tick. Gameplay systems read this instead of
+/// `Time`, their `delta` is always exactly one tick.
+#[derive(Resource, Default)]
+pub struct SimulationTime {
+    /// frame time not simulated yet
+    accumulator: Duration,
+}
+
+impl SimulationTime {
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(SIMULATION_STEP)
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        SIMULATION_STEP
+    }
+
+    /// How far the frame is between the last tick and the next, from 0 to 1.
+    pub fn overstep(&self) -> f32 {
+        self.accumulator.as_secs_f32() / SIMULATION_STEP
+    }
+
+    /// Adds a frame, a long hitch only catches up on so many ticks.
+    pub fn advance(&mut self, delta: Duration) {
+        self.accumulator = (self.accumulator + delta)
+            .min(Duration::from_secs_f32(MAX_SIMULATION_LAG));
+    }
+
+    /// Takes a tick out of the accumulated time, if there's enough.
+    pub fn expend(&mut self) -> bool {
+        match self.accumulator.checked_sub(self.delta()) {
+            Some(left) => {
+                self.accumulator = left;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Buttons only pressed or released for a single frame, held until the next
+/// simulation tick takes them. Frames can go by without a tick.
+#[derive(Resource, Default)]
+pub struct InputLatch {
+    pub fire_released: bool,
+    pub bomb: bool,
+    pub dash: bool,
+}
+
+/// Hands out `SpawnIndex`es, starting over with every run. Like `GameRng`,
+/// systems spawning with it must sit in a fixed order.
+#[derive(Resource, Default)]
+pub struct SpawnCounter(u64);
+
+impl SpawnCounter {
+    pub fn next(&mut self) -> SpawnIndex {
+        self.0 += 1;
+        SpawnIndex(self.0)
+    }
+}
+
+/// The one source of randomness for gameplay, so a run can be replayed from
+/// its seed. Reseeded at the start of every run, with the seed given on the
+/// command line if there was one. Systems using it must sit in different
+/// `SimulationStep`s, or the numbers they get depend on which ran first.
+#[derive(Resource)]
+pub struct GameRng {
+    /// from `--seed`, every run uses it
+    fixed_seed: Option<u64>,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(rand::random);
+        Self {
+            fixed_seed,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts over from the fixed seed, or a fresh one without it.
+    pub fn start_run(&mut self) {
+        *self = Self::new(self.fixed_seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn ticks(simulation_time: &mut SimulationTime) -> u32 {
+        let mut ticks = 0;
+        while simulation_time.expend() {
+            ticks += 1;
+        }
+        ticks
+    }
+
+    #[test]
+    fn same_ticks_whatever_the_frame_rate() {
+        let step = Duration::from_secs_f32(SIMULATION_STEP);
+        let mut slow = SimulationTime::default();
+        let mut fast = SimulationTime::default();
+        let (mut slow_ticks, mut fast_ticks) = (0, 0);
+        for _ in 0..30 {
+            slow.advance(step * 4);
+            slow_ticks += ticks(&mut slow);
+        }
+        for _ in 0..240 {
+            fast.advance(step / 2);
+            fast_ticks += ticks(&mut fast);
+        }
+        // rounding to whole nanoseconds can leave the last tick just short
+        assert!((119..=120).contains(&slow_ticks));
+        assert!((119..=120).contains(&fast_ticks));
+    }
+
+    #[test]
+    fn hitches_only_catch_up_so_far() {
+        let mut simulation_time = SimulationTime::default();
+        simulation_time.advance(Duration::from_secs(5));
+        let most = (MAX_SIMULATION_LAG / SIMULATION_STEP).round() as u32;
+        assert!(ticks(&mut simulation_time) <= most);
+        assert!(simulation_time.overstep() < 1.);
+    }
+
+    fn rolls(rng: &mut GameRng) -> Vec<u32> {
+        (0..8).map(|_| rng.gen_range(0..1000)).collect()
+    }
+
+    #[test]
+    fn seed_replays_the_run() {
+        let mut a = GameRng::new(Some(42));
+        let mut b = GameRng::new(Some(42));
+        assert_eq!(a.seed(), 42);
+        assert_eq!(rolls(&mut a), rolls(&mut b));
+    }
+
+    #[test]
+    fn start_run_starts_over() {
+        let mut fixed = GameRng::new(Some(42));
+        let first = rolls(&mut fixed);
+        fixed.start_run();
+        assert_eq!(fixed.seed(), 42);
+        assert_eq!(rolls(&mut fixed), first);
+
+        // without --seed every run gets a fresh seed
+        let mut random = GameRng::new(None);
+        let seed = random.seed();
+        random.start_run();
+        assert_ne!(random.seed(), seed);
+    }
+
+    #[test]
+    fn shield_absorbs_until_spent() {
+        let mut shield = Shield::default();
+        shield.upgrade();
+        assert_eq!(shield.charges, 2);
+        assert!(shield.absorb());
+        assert!(shield.absorb());
+        assert!(!shield.absorb());
+        assert_eq!(shield.charges, 0);
+    }
+
+    #[test]
+    fn hits_hold_off_regeneration() {
+        let mut shield = Shield::default();
+        shield.absorb();
+        let second = Duration::from_secs(1);
+        for _ in 0..(SHIELD_REGEN_DELAY + SHIELD_REGEN_INTERVAL) as u32 - 1 {
+            shield.regenerate(second);
+        }
+        // a hit on a spent shield still starts the wait over
+        assert!(!shield.absorb());
+        for _ in 0..SHIELD_REGEN_DELAY as u32 {
+            shield.regenerate(second);
+        }
+        assert_eq!(shield.charges, 0);
+        for _ in 0..SHIELD_REGEN_INTERVAL.ceil() as u32 {
+            shield.regenerate(second);
+        }
+        assert_eq!(shield.charges, 1);
+    }
+}
@@ -1,24 +1,68 @@
 use crate::prelude::*;
-use bevy::{prelude::*, utils::HashSet};
+use bevy::{audio::AudioSink, prelude::*, utils::HashSet};
 
 pub struct SharedPlugin;
 
 impl Plugin for SharedPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(play_theme_song)
-            .add_startup_system(spawn_simple_ui_system)
-            .add_system(update_simple_ui_system)
-            .add_system(movement_system)
-            .add_system(auto_despawner_system)
-            .add_system(handle_explosion_event_system)
-            .add_system(explosion_animation_system)
-            .add_event::<ExplosionEvent>();
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(spawn_simple_ui_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Movement,
+                SystemSet::new().with_system(movement_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new().with_system(auto_despawner_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(update_simple_ui_system)
+                    .with_system(handle_explosion_event_system)
+                    .with_system(handle_batch_explosions_event_system)
+                    .with_system(explosion_animation_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(despawn_with::<UIFont>)
+                    .with_system(despawn_with::<Laser>),
+            )
+            // let the final explosions play out behind the game over screen
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(handle_explosion_event_system)
+                    .with_system(handle_batch_explosions_event_system)
+                    .with_system(explosion_animation_system),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused)
+                    .with_system(pause_music_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(resume_music_system),
+            )
+            .add_event::<ExplosionEvent>()
+            .add_event::<BatchExplosionsEvent>();
+    }
+}
+
+/// Despawns every entity tagged with `T`, used to tear down screens.
+pub fn despawn_with<T: Component>(
+    mut commands: Commands,
+    query: Query<Entity, With<T>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
 fn movement_system(
     mut query: Query<(Entity, &mut Transform, &Movable, &Velocity)>,
-    time: Res<Time>,
+    time: Res<SimulationTime>,
 ) {
     for (_entity, mut tf, _movable, velocity) in query.iter_mut() {
         tf.translation.x += velocity.0.x * time.delta_seconds() * BASE_SPEED;
@@ -28,7 +72,10 @@ fn movement_system(
 
 fn auto_despawner_system(
     mut commands: Commands,
-    query: Query<(Entity, &Transform, &Collision, &Movable), Without<Enemy>>,
+    query: Query<
+        (Entity, &Transform, &Collision, &Movable),
+        (Without<Enemy>, Without<Bounce>),
+    >,
     window_size: Res<WindowSize>,
 ) {
     let mut despawned: HashSet<Entity> = HashSet::new();
@@ -75,7 +122,25 @@ fn handle_explosion_event_system(
             },
             Explosion(Timer::from_seconds(0.05, TimerMode::Once)),
         ));
-        audio.play(audio_assets.explosion.clone());
+        if event.with_sound {
+            audio.play(audio_assets.explosion.clone());
+        }
+    }
+}
+
+/// Blows up every position of a batch at once, with a single sound for the
+/// lot.
+fn handle_batch_explosions_event_system(
+    mut batch_events: EventReader<BatchExplosionsEvent>,
+    mut explosion_event: EventWriter<ExplosionEvent>,
+) {
+    for event in batch_events.iter() {
+        for (i, position) in event.positions.iter().enumerate() {
+            explosion_event.send(ExplosionEvent {
+                position: position.truncate(),
+                with_sound: event.with_sound && i == 0,
+            });
+        }
     }
 }
 
@@ -98,8 +163,13 @@ fn explosion_animation_system(
     }
 }
 
-fn play_theme_song(audio_assets: Res<AudioAssets>, audio: Res<Audio>) {
-    audio.play_with_settings(
+fn play_theme_song(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let sink = audio.play_with_settings(
         audio_assets.theme_song.clone(),
         PlaybackSettings {
             repeat: true,
@@ -107,6 +177,26 @@ fn play_theme_song(audio_assets: Res<AudioAssets>, audio: Res<Audio>) {
             ..default()
         },
     );
+    // the returned handle is weak, hold a strong one so the sink stays alive
+    commands.insert_resource(MusicController(audio_sinks.get_handle(sink)));
+}
+
+fn pause_music_system(
+    music: Res<MusicController>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if let Some(sink) = audio_sinks.get(&music.0) {
+        sink.pause();
+    }
+}
+
+fn resume_music_system(
+    music: Res<MusicController>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if let Some(sink) = audio_sinks.get(&music.0) {
+        sink.play();
+    }
 }
 
 fn spawn_simple_ui_system(
@@ -117,7 +207,7 @@ fn spawn_simple_ui_system(
         // Create a TextBundle that has a Text with a single section.
         TextBundle::from_section(
             // Accepts a `String` or any type that converts into a `String`, such as `&str`
-            "HP: 0\nSCORE: 0\nLevel: 0",
+            "HP: 0\nSCORE: 0\nLevel: 0\nWAVE: 1\nGUN: 1\nBOMBS: 0\nDASH: READY",
             TextStyle {
                 font: font_assets.ui.clone(),
                 font_size: 20.,
@@ -141,12 +231,30 @@ fn spawn_simple_ui_system(
 
 fn update_simple_ui_system(
     mut query: Query<&mut Text, With<UIFont>>,
+    query_player: Query<(&Weapon, &Dash), With<Player>>,
     player_state: Res<PlayerState>,
 ) {
+    let (gun, dash) = match query_player.get_single() {
+        Ok((weapon, dash)) if dash.cooldown.finished() => {
+            (weapon.level.number(), "READY".to_owned())
+        }
+        Ok((weapon, dash)) => (
+            weapon.level.number(),
+            format!("{:.1}", dash.cooldown.remaining_secs()),
+        ),
+        Err(_) => (0, "-".to_owned()),
+    };
     for mut text in &mut query {
         text.sections[0].value = format!(
-            "HP: {}\nSCORE: {}\nLevel: {}",
-            player_state.health, player_state.score, player_state.golds
+            "HP: {}\nSCORE: {}\nLevel: {}\nWAVE: {}\nGUN: {}\nBOMBS: {}\n\
+             DASH: {}",
+            player_state.health,
+            player_state.score,
+            player_state.golds,
+            player_state.waves + 1,
+            gun,
+            player_state.bombs,
+            dash
         );
     }
 }
@@ -0,0 +1,72 @@
+use crate::prelude::*;
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Shield::default())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_shield_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new().with_system(shield_regen_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(spawn_shield_bubble_system)
+                    .with_system(update_shield_bubble_system),
+            );
+    }
+}
+
+fn reset_shield_system(mut shield: ResMut<Shield>) {
+    *shield = Shield::default();
+}
+
+fn shield_regen_system(
+    mut shield: ResMut<Shield>,
+    time: Res<SimulationTime>,
+) {
+    shield.regenerate(time.delta());
+}
+
+fn spawn_shield_bubble_system(
+    mut commands: Commands,
+    query: Query<Entity, Added<Player>>,
+    shape_assets: Res<ShapeAssets>,
+) {
+    for player in query.iter() {
+        let bubble = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: shape_assets.shield_bubble.clone().into(),
+                    material: shape_assets.shield_material.clone(),
+                    transform: Transform::from_xyz(0., 0., 1.),
+                    ..default()
+                },
+                ShieldBubble,
+            ))
+            .id();
+        commands.entity(player).add_child(bubble);
+    }
+}
+
+fn update_shield_bubble_system(
+    shield: Res<Shield>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<
+        (&Handle<ColorMaterial>, &mut Visibility),
+        With<ShieldBubble>,
+    >,
+) {
+    let fill = shield.charges as f32 / shield.capacity.max(1) as f32;
+    for (handle, mut visibility) in query.iter_mut() {
+        visibility.is_visible = shield.charges > 0;
+        if let Some(material) = materials.get_mut(handle) {
+            material.color.set_a(SHIELD_COLOR.a() * fill);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::prelude::*;
+use bevy::{
+    ecs::{event::Event, schedule::ShouldRun},
+    prelude::*,
+    transform::TransformSystem,
+};
+
+/// Runs gameplay on a fixed tick, as many times per frame as the frame took,
+/// so everything behaves the same whatever the frame rate. Sprites are
+/// drawn in between the last two ticks to keep motion smooth.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationTime::default())
+            .insert_resource(InputLatch::default())
+            .insert_resource(SpawnCounter::default())
+            .add_stage_after(
+                CoreStage::Update,
+                SimulationStage,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                restore_transforms_system.at_start(),
+            )
+            .add_system_to_stage(
+                SimulationStage,
+                record_transforms_system.at_end(),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_transforms_system
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_simulation_system),
+            );
+    }
+}
+
+/// Gameplay systems go here instead of `SystemSet::on_update(Playing)`, the
+/// stage only ticks while playing. Add them with `add_simulation_systems`,
+/// read `SimulationTime` instead of `Time` and `InputLatch` for anything
+/// that is only pressed for a frame.
+#[derive(StageLabel)]
+pub struct SimulationStage;
+
+/// The steps of a tick, in order. Systems within a step run in any order,
+/// so anything that depends on another system goes in a later step.
+#[derive(SystemLabel, Debug, Clone, Copy)]
+pub enum SimulationStep {
+    /// reading the player's controls
+    Input,
+    /// waves and bosses showing up
+    Spawn,
+    /// movement patterns, homing and dashes setting velocities
+    Steering,
+    /// weapons going off
+    Fire,
+    Movement,
+    Collision,
+    /// collisions turned into hits and pickups
+    Hits,
+    /// hits turned into damage, kills and drops
+    Damage,
+    /// despawning and bookkeeping once everything else is done
+    Cleanup,
+}
+
+impl SimulationStep {
+    const ALL: [Self; 9] = [
+        Self::Input,
+        Self::Spawn,
+        Self::Steering,
+        Self::Fire,
+        Self::Movement,
+        Self::Collision,
+        Self::Hits,
+        Self::Damage,
+        Self::Cleanup,
+    ];
+
+    fn previous(self) -> Option<Self> {
+        (self as usize).checked_sub(1).map(|index| Self::ALL[index])
+    }
+}
+
+pub trait SimulationAppExt {
+    /// Gameplay systems running during `step` of every tick.
+    fn add_simulation_systems(
+        &mut self,
+        step: SimulationStep,
+        systems: SystemSet,
+    ) -> &mut Self;
+
+    /// Events sent and read by the simulation. They are kept for two ticks
+    /// instead of two frames, so none get lost when a frame runs no tick or
+    /// several of them.
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl SimulationAppExt for App {
+    fn add_simulation_systems(
+        &mut self,
+        step: SimulationStep,
+        systems: SystemSet,
+    ) -> &mut Self {
+        let systems = match step.previous() {
+            Some(previous) => systems.label(step).after(previous),
+            None => systems.label(step),
+        };
+        self.add_system_set_to_stage(SimulationStage, systems)
+    }
+
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<Events<T>>() {
+            self.init_resource::<Events<T>>().add_system_to_stage(
+                SimulationStage,
+                update_simulation_events::<T>.at_end(),
+            );
+        }
+        self
+    }
+}
+
+fn update_simulation_events<T: Event>(world: &mut World) {
+    world.resource_mut::<Events<T>>().update();
+}
+
+fn simulation_tick(
+    mut simulation_time: ResMut<SimulationTime>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    // paused or in a menu, keep the leftover for when the game resumes
+    if *state.current() != GameState::Playing {
+        *looping = false;
+        return ShouldRun::No;
+    }
+    // something in the last tick asked for another state, like the player
+    // dying, stop before simulating past it
+    if *looping && state.is_changed() {
+        *looping = false;
+        return ShouldRun::No;
+    }
+    // the criteria is asked again after every tick, the frame counts once
+    if !*looping {
+        simulation_time.advance(time.delta());
+        *looping = true;
+    }
+    if simulation_time.expend() {
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        ShouldRun::No
+    }
+}
+
+fn reset_simulation_system(
+    mut simulation_time: ResMut<SimulationTime>,
+    mut input_latch: ResMut<InputLatch>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    mut rng: ResMut<GameRng>,
+) {
+    *simulation_time = SimulationTime::default();
+    *input_latch = InputLatch::default();
+    *spawn_counter = SpawnCounter::default();
+    rng.start_run();
+}
+
+/// Undoes the interpolation of the last frame, the simulation carries on
+/// from where the last tick left things.
+fn restore_transforms_system(world: &mut World) {
+    let mut query = world.query::<(&mut Transform, &Interpolated)>();
+    for (mut tf, interpolated) in query.iter_mut(world) {
+        *tf = interpolated.current;
+    }
+}
+
+fn record_transforms_system(world: &mut World) {
+    let mut query = world.query::<(&Transform, &mut Interpolated)>();
+    for (tf, mut interpolated) in query.iter_mut(world) {
+        interpolated.previous = interpolated.current;
+        interpolated.current = *tf;
+    }
+
+    // whatever started moving this tick starts where it was spawned
+    let mut query = world.query_filtered::<(Entity, &Transform), (
+        Or<(With<Movable>, With<Player>)>,
+        Without<Interpolated>,
+    )>();
+    let spawned: Vec<(Entity, Transform)> = query
+        .iter(world)
+        .map(|(entity, tf)| (entity, *tf))
+        .collect();
+    for (entity, tf) in spawned {
+        world.entity_mut(entity).insert(Interpolated {
+            previous: tf,
+            current: tf,
+        });
+    }
+}
+
+fn interpolate_transforms_system(
+    simulation_time: Res<SimulationTime>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let t = simulation_time.overstep();
+    for (mut tf, interpolated) in query.iter_mut() {
+        let (previous, current) = (interpolated.previous, interpolated.current);
+        // wrapping around the screen shouldn't streak across it
+        if previous.translation.distance(current.translation)
+            > INTERPOLATION_SNAP_DISTANCE
+        {
+            *tf = current;
+            continue;
+        }
+        tf.translation = previous.translation.lerp(current.translation, t);
+        tf.rotation = previous.rotation.slerp(current.rotation, t);
+        tf.scale = current.scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Ticks(u32);
+
+    fn count_ticks_system(mut ticks: ResMut<Ticks>) {
+        ticks.0 += 1;
+    }
+
+    fn game_over_system(mut state: ResMut<State<GameState>>) {
+        let _ = state.set(GameState::GameOver);
+    }
+
+    fn app(state: GameState) -> App {
+        let mut time = Time::default();
+        let startup = time.startup();
+        time.update_with_instant(startup);
+
+        let mut app = App::new();
+        app.add_state(state)
+            .insert_resource(time)
+            .insert_resource(GameRng::new(Some(0)))
+            .init_resource::<Ticks>()
+            .add_plugin(SimulationPlugin)
+            .add_simulation_systems(
+                SimulationStep::Cleanup,
+                SystemSet::new().with_system(count_ticks_system),
+            );
+        app
+    }
+
+    /// Runs a frame that took a little longer than `ticks` steps.
+    fn frame(app: &mut App, ticks: u32) -> u32 {
+        let mut time = app.world.resource_mut::<Time>();
+        let last = time.last_update().unwrap();
+        let took = Duration::from_secs_f32(SIMULATION_STEP) * ticks
+            + Duration::from_millis(1);
+        time.update_with_instant(last + took);
+        app.update();
+        app.world.resource::<Ticks>().0
+    }
+
+    #[test]
+    fn ticks_for_every_step_of_the_frame() {
+        let mut app = app(GameState::Playing);
+        assert_eq!(frame(&mut app, 3), 3);
+        assert_eq!(frame(&mut app, 0), 3);
+        assert_eq!(frame(&mut app, 1), 4);
+    }
+
+    #[test]
+    fn no_ticks_outside_of_playing() {
+        let mut app = app(GameState::Paused);
+        assert_eq!(frame(&mut app, 3), 0);
+    }
+
+    #[test]
+    fn stops_once_the_state_changes() {
+        let mut app = app(GameState::Playing);
+        app.add_simulation_systems(
+            SimulationStep::Damage,
+            SystemSet::new().with_system(game_over_system),
+        );
+        assert_eq!(frame(&mut app, 3), 1);
+        assert_eq!(frame(&mut app, 3), 1);
+        let state = app.world.resource::<State<GameState>>();
+        assert_eq!(*state.current(), GameState::GameOver);
+    }
+}
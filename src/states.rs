@@ -0,0 +1,10 @@
+/// Top level screens of the game. Every plugin registers its systems
+/// against one (or more) of these so nothing ticks outside of its screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Loading,
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+}
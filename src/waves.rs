@@ -0,0 +1,463 @@
+use crate::boss::spawn_boss;
+use crate::enemy::{enemy_weapon, spawn_enemy};
+use crate::prelude::*;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use rand::Rng;
+use serde::Deserialize;
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<StageAsset>()
+            .init_asset_loader::<StageAssetLoader>()
+            .insert_resource(WaveDirector::default())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(reset_wave_director_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Spawn,
+                SystemSet::new().with_system(wave_director_system),
+            )
+            .add_simulation_systems(
+                SimulationStep::Steering,
+                SystemSet::new().with_system(follow_path_system),
+            );
+    }
+}
+
+/// A list of waves played in order, looping back to the first one once the
+/// last wave is cleared, with a boss fight every `boss_every` waves (never
+/// when 0). Positions are normalized to the window: `(0, 0)` is the center,
+/// `(-1, 1)` the top left corner.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "5b0f7f4e-8d7a-4c3e-9a57-2f6c1d0b8e31"]
+pub struct StageAsset {
+    #[serde(default = "default_boss_every")]
+    pub boss_every: u32,
+    pub waves: Vec<WaveDefinition>,
+}
+
+fn default_boss_every() -> u32 {
+    BOSS_WAVE_INTERVAL
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WaveDefinition {
+    pub name: String,
+    pub groups: Vec<SpawnGroup>,
+}
+
+/// `count` enemies of `kind` laid out in `formation` around `entry`, the
+/// first one spawned `delay` seconds into the wave and the rest every
+/// `interval` seconds after that.
+#[derive(Debug, Deserialize)]
+pub struct SpawnGroup {
+    pub delay: f32,
+    pub kind: EnemyKind,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub interval: f32,
+    pub entry: (f32, f32),
+    #[serde(default)]
+    pub formation: Formation,
+    /// waypoints followed after spawning, then the enemy drifts down
+    #[serde(default)]
+    pub path: Vec<(f32, f32)>,
+    /// ignored when a `path` is given
+    #[serde(default)]
+    pub pattern: Option<MovementPattern>,
+    /// replaces the kind's own fire pattern
+    #[serde(default)]
+    pub fire: Option<FirePattern>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy)]
+pub enum Formation {
+    #[default]
+    Single,
+    Line {
+        spacing: f32,
+    },
+    Column {
+        spacing: f32,
+    },
+    Vee {
+        spacing: f32,
+    },
+    Random,
+}
+
+impl Formation {
+    /// Offset in pixels of the `index`-th ship of a group of `count` from
+    /// the group's entry point. `Random` is rolled by the director instead.
+    fn offset(&self, index: u32, count: u32) -> Vec2 {
+        let centered = index as f32 - (count as f32 - 1.) / 2.;
+        match *self {
+            Formation::Single | Formation::Random => Vec2::ZERO,
+            Formation::Line { spacing } => Vec2::new(centered * spacing, 0.),
+            Formation::Column { spacing } => {
+                Vec2::new(0., index as f32 * spacing)
+            }
+            Formation::Vee { spacing } => Vec2::new(
+                centered * spacing,
+                centered.abs() * spacing / 2.,
+            ),
+        }
+    }
+}
+
+/// How an enemy moves once spawned, on top of drifting down at its kind's
+/// speed. Positions are normalized like everywhere else in the stage, speeds
+/// are in `BASE_SPEED` units, other distances are pixels and times seconds.
+#[derive(Debug, Deserialize, Clone)]
+pub enum MovementPattern {
+    SineWeave { amplitude: f32, frequency: f32 },
+    ZigZag { width: f32, period: f32 },
+    SwoopHover { hover_y: f32, hover_time: f32 },
+    Dive { delay: f32, speed: f32 },
+    Spline { points: Vec<(f32, f32)>, duration: f32 },
+    Orbit { radius: f32, angular_speed: f32, duration: f32 },
+}
+
+impl MovementPattern {
+    /// Adds the pattern's component to a freshly spawned enemy. `offset` is
+    /// the enemy's place in its formation, so splines keep the shape.
+    fn insert(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        position: Vec2,
+        offset: Vec2,
+        velocity: Vec2,
+        window_size: &WindowSize,
+    ) {
+        let speed = velocity.length();
+        let mut entity = commands.entity(entity);
+        match self.clone() {
+            MovementPattern::SineWeave {
+                amplitude,
+                frequency,
+            } => entity.insert(SineWeave {
+                amplitude,
+                frequency,
+                elapsed: 0.,
+            }),
+            MovementPattern::ZigZag { width, period } => entity.insert(ZigZag {
+                width,
+                period,
+                elapsed: 0.,
+            }),
+            MovementPattern::SwoopHover {
+                hover_y,
+                hover_time,
+            } => entity.insert(SwoopHover {
+                hover_y: hover_y * window_size.height / 2. + offset.y,
+                hover_time,
+                speed,
+                phase: SwoopPhase::Swoop,
+                elapsed: 0.,
+            }),
+            MovementPattern::Dive { delay, speed } => {
+                entity.insert(DiveAtPlayer {
+                    delay,
+                    speed,
+                    elapsed: 0.,
+                    diving: false,
+                })
+            }
+            MovementPattern::Spline { points, duration } => {
+                let points = points
+                    .into_iter()
+                    .map(|point| to_screen(point, window_size) + offset);
+                let points = std::iter::once(position).chain(points).collect();
+                entity.insert(SplinePath {
+                    points,
+                    duration,
+                    speed,
+                    elapsed: 0.,
+                })
+            }
+            MovementPattern::Orbit {
+                radius,
+                angular_speed,
+                duration,
+            } => entity.insert(Orbit {
+                // start at the top of the circle
+                center: position - Vec2::new(0., radius),
+                radius,
+                angular_speed,
+                angle: std::f32::consts::FRAC_PI_2,
+                duration,
+                drift: velocity,
+                elapsed: 0.,
+            }),
+        };
+    }
+}
+
+#[derive(Default)]
+pub struct StageAssetLoader;
+
+impl AssetLoader for StageAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let stage = ron::de::from_bytes::<StageAsset>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(stage));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stage.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct WaveAssets {
+    pub stage: Handle<StageAsset>,
+}
+
+/// Progress through the current wave of the stage.
+#[derive(Resource, Default)]
+pub struct WaveDirector {
+    pub wave: usize,
+    pub elapsed: f32,
+    /// how many enemies each group of the current wave has spawned so far
+    pub spawned: Vec<u32>,
+    /// waves are on hold until the boss is beaten
+    pub boss_fight: bool,
+}
+
+fn reset_wave_director_system(mut director: ResMut<WaveDirector>) {
+    *director = WaveDirector::default();
+}
+
+fn to_screen(position: (f32, f32), window_size: &WindowSize) -> Vec2 {
+    Vec2::new(
+        position.0 * window_size.width / 2.,
+        position.1 * window_size.height / 2.,
+    )
+}
+
+fn wave_director_system(
+    mut commands: Commands,
+    mut director: ResMut<WaveDirector>,
+    mut enemy_count: ResMut<EnemyCount>,
+    mut wave_complete_event: EventWriter<WaveCompleteEvent>,
+    wave_assets: Res<WaveAssets>,
+    stages: Res<Assets<StageAsset>>,
+    game_textures: Res<GameTextures>,
+    enemy_attrs: Res<EnemyAttributes>,
+    window_size: Res<WindowSize>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_counter: ResMut<SpawnCounter>,
+    time: Res<SimulationTime>,
+) {
+    let stage = match stages.get(&wave_assets.stage) {
+        Some(stage) if !stage.waves.is_empty() => stage,
+        _ => return,
+    };
+
+    if director.boss_fight {
+        if enemy_count.0 <= 0 {
+            wave_complete_event.send_default();
+            director.boss_fight = false;
+        }
+        return;
+    }
+
+    let wave = &stage.waves[director.wave % stage.waves.len()];
+    if director.spawned.len() != wave.groups.len() {
+        info!("wave {}: {}", director.wave + 1, wave.name);
+        director.spawned = vec![0; wave.groups.len()];
+    }
+
+    director.elapsed += time.delta_seconds();
+    let elapsed = director.elapsed;
+    for (group, spawned) in wave.groups.iter().zip(director.spawned.iter_mut())
+    {
+        while *spawned < group.count
+            && elapsed >= group.delay + *spawned as f32 * group.interval
+        {
+            let definition = group.kind.definition();
+            let offset = match group.formation {
+                Formation::Random => {
+                    let half_width =
+                        (window_size.width - definition.hitbox().x) / 2.;
+                    let x = rng.gen_range(-half_width..half_width);
+                    Vec2::new(x - to_screen(group.entry, &window_size).x, 0.)
+                }
+                formation => formation.offset(*spawned, group.count),
+            };
+            let position = to_screen(group.entry, &window_size) + offset;
+            let velocity = enemy_attrs.velocity * definition.speed;
+
+            let entity = spawn_enemy(
+                &mut commands,
+                &game_textures,
+                &mut rng,
+                &mut spawn_counter,
+                group.kind,
+                position,
+                velocity,
+            );
+            if !group.path.is_empty() {
+                // the whole formation follows the path, keeping its shape
+                let waypoints = group
+                    .path
+                    .iter()
+                    .map(|point| to_screen(*point, &window_size) + offset)
+                    .collect();
+                commands.entity(entity).insert(FollowPath {
+                    waypoints,
+                    next: 0,
+                    speed: velocity.length(),
+                });
+            } else if let Some(pattern) = &group.pattern {
+                pattern.insert(
+                    &mut commands,
+                    entity,
+                    position,
+                    offset,
+                    velocity,
+                    &window_size,
+                );
+            }
+            if let Some(fire) = group.fire {
+                commands
+                    .entity(entity)
+                    .insert(enemy_weapon(definition, fire, &mut rng));
+            }
+
+            enemy_count.0 += 1;
+            *spawned += 1;
+        }
+    }
+
+    let all_spawned = wave
+        .groups
+        .iter()
+        .zip(director.spawned.iter())
+        .all(|(group, spawned)| *spawned >= group.count);
+    if all_spawned && enemy_count.0 <= 0 {
+        wave_complete_event.send_default();
+        director.wave += 1;
+        director.elapsed = 0.;
+        director.spawned.clear();
+
+        let boss_every = stage.boss_every as usize;
+        if boss_every > 0 && director.wave.is_multiple_of(boss_every) {
+            info!("boss after wave {}", director.wave);
+            spawn_boss(
+                &mut commands,
+                &mut spawn_counter,
+                &game_textures,
+                &window_size,
+                enemy_attrs.velocity * EnemyKind::Boss.definition().speed,
+            );
+            enemy_count.0 += 1;
+            director.boss_fight = true;
+        }
+    }
+}
+
+fn follow_path_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &mut Velocity, &mut FollowPath)>,
+) {
+    for (entity, tf, mut velocity, mut path) in query.iter_mut() {
+        let position = tf.translation.truncate();
+        if position.distance(path.waypoints[path.next]) < PATH_WAYPOINT_RADIUS
+        {
+            path.next += 1;
+            if path.next >= path.waypoints.len() {
+                velocity.0 = Vec2::new(0., -path.speed);
+                commands.entity(entity).remove::<FollowPath>();
+                continue;
+            }
+        }
+        velocity.0 = (path.waypoints[path.next] - position).normalize_or_zero()
+            * path.speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_stage_parses() {
+        let stage: StageAsset = ron::de::from_str(include_str!(
+            "../assets/waves/endless.stage.ron"
+        ))
+        .unwrap();
+        assert!(stage.boss_every > 0);
+        assert!(!stage.waves.is_empty());
+        assert!(stage.waves.iter().all(|wave| !wave.groups.is_empty()));
+    }
+
+    #[test]
+    fn stage_fields_have_defaults() {
+        let stage: StageAsset = ron::de::from_str(
+            "(waves: [(name: \"One\", groups: [
+                (delay: 1.0, kind: Gunner, entry: (0.0, 1.1)),
+                (
+                    delay: 2.0,
+                    kind: Drone,
+                    count: 3,
+                    entry: (0.5, 1.1),
+                    formation: Column(spacing: 30.0),
+                    pattern: Some(ZigZag(width: 40.0, period: 1.0)),
+                ),
+            ])])",
+        )
+        .unwrap();
+        assert_eq!(stage.boss_every, BOSS_WAVE_INTERVAL);
+
+        let [single, column] = &stage.waves[0].groups[..] else {
+            panic!("expected two groups");
+        };
+        assert_eq!(single.kind, EnemyKind::Gunner);
+        assert_eq!(single.count, 1);
+        assert_eq!(single.interval, 0.);
+        assert!(matches!(single.formation, Formation::Single));
+        assert!(single.path.is_empty());
+        assert!(single.pattern.is_none() && single.fire.is_none());
+
+        assert_eq!(column.count, 3);
+        match column.formation {
+            Formation::Column { spacing } => assert_eq!(spacing, 30.),
+            other => panic!("expected a column, got {other:?}"),
+        }
+        assert!(matches!(
+            column.pattern,
+            Some(MovementPattern::ZigZag { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_enemies_are_rejected() {
+        let stage = ron::de::from_str::<StageAsset>(
+            "(waves: [(name: \"One\", groups: [
+                (delay: 1.0, kind: Dragon, entry: (0.0, 1.1)),
+            ])])",
+        );
+        assert!(stage.is_err());
+    }
+}
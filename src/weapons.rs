@@ -0,0 +1,195 @@
+use crate::prelude::*;
+use crate::projectiles::ProjectileBehaviour;
+
+/// What a single player projectile looks like and how it behaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShotKind {
+    Laser,
+    Beam,
+    Missile,
+}
+
+impl ShotKind {
+    pub fn size(self) -> (f32, f32) {
+        match self {
+            ShotKind::Laser => SIZE_LASER_PLAYER,
+            ShotKind::Beam => SIZE_BEAM_PLAYER,
+            ShotKind::Missile => SIZE_MISSILE_PLAYER,
+        }
+    }
+}
+
+/// One projectile of a volley. `offset` is horizontal from the center of the
+/// ship in pixels, `angle` in radians from straight up, positive to the
+/// right, and `speed` in `BASE_SPEED` units.
+#[derive(Debug)]
+pub struct ShotDefinition {
+    pub kind: ShotKind,
+    pub offset: f32,
+    pub angle: f32,
+    pub speed: f32,
+    pub behaviour: ProjectileBehaviour,
+}
+
+#[derive(Debug)]
+pub struct WeaponDefinition {
+    /// damage of every projectile in the volley
+    pub damage: i32,
+    /// seconds before the weapon can fire again
+    pub fire_interval: f32,
+    pub fire_mode: FireMode,
+    pub shots: &'static [ShotDefinition],
+}
+
+const BEAM: ProjectileBehaviour = ProjectileBehaviour {
+    pierce: 2,
+    ..ProjectileBehaviour::NONE
+};
+
+const MISSILE: ProjectileBehaviour = ProjectileBehaviour {
+    splash: MISSILE_SPLASH_RADIUS,
+    homing: MISSILE_TURN_RATE,
+    ..ProjectileBehaviour::NONE
+};
+
+static SINGLE: WeaponDefinition = WeaponDefinition {
+    damage: 1,
+    fire_interval: 0.18,
+    fire_mode: FireMode::Auto,
+    shots: &[ShotDefinition {
+        kind: ShotKind::Laser,
+        offset: 0.,
+        angle: 0.,
+        speed: 1.5,
+        behaviour: ProjectileBehaviour::NONE,
+    }],
+};
+
+static DOUBLE: WeaponDefinition = WeaponDefinition {
+    damage: 1,
+    fire_interval: 0.18,
+    fire_mode: FireMode::Auto,
+    shots: &[
+        ShotDefinition {
+            kind: ShotKind::Laser,
+            offset: -10.,
+            angle: 0.,
+            speed: 1.5,
+            behaviour: ProjectileBehaviour::NONE,
+        },
+        ShotDefinition {
+            kind: ShotKind::Laser,
+            offset: 10.,
+            angle: 0.,
+            speed: 1.5,
+            behaviour: ProjectileBehaviour::NONE,
+        },
+    ],
+};
+
+static TRIPLE_SPREAD: WeaponDefinition = WeaponDefinition {
+    damage: 1,
+    fire_interval: 0.2,
+    fire_mode: FireMode::Auto,
+    shots: &[
+        ShotDefinition {
+            kind: ShotKind::Laser,
+            offset: -6.,
+            angle: -0.2,
+            speed: 1.5,
+            behaviour: ProjectileBehaviour::NONE,
+        },
+        ShotDefinition {
+            kind: ShotKind::Laser,
+            offset: 0.,
+            angle: 0.,
+            speed: 1.5,
+            behaviour: ProjectileBehaviour::NONE,
+        },
+        ShotDefinition {
+            kind: ShotKind::Laser,
+            offset: 6.,
+            angle: 0.2,
+            speed: 1.5,
+            behaviour: ProjectileBehaviour::NONE,
+        },
+    ],
+};
+
+static WIDE_BEAM: WeaponDefinition = WeaponDefinition {
+    damage: 3,
+    fire_interval: 0.45,
+    fire_mode: FireMode::TapBurst {
+        shots: 3,
+        interval: 0.08,
+    },
+    shots: &[ShotDefinition {
+        kind: ShotKind::Beam,
+        offset: 0.,
+        angle: 0.,
+        speed: 2.,
+        behaviour: BEAM,
+    }],
+};
+
+static HOMING_MISSILES: WeaponDefinition = WeaponDefinition {
+    damage: 3,
+    fire_interval: 0.28,
+    fire_mode: FireMode::Auto,
+    shots: &[
+        ShotDefinition {
+            kind: ShotKind::Beam,
+            offset: 0.,
+            angle: 0.,
+            speed: 2.,
+            behaviour: BEAM,
+        },
+        ShotDefinition {
+            kind: ShotKind::Missile,
+            offset: -18.,
+            angle: -0.6,
+            speed: 1.,
+            behaviour: MISSILE,
+        },
+        ShotDefinition {
+            kind: ShotKind::Missile,
+            offset: 18.,
+            angle: 0.6,
+            speed: 1.,
+            behaviour: MISSILE,
+        },
+    ],
+};
+
+impl WeaponLevel {
+    pub const ALL: [WeaponLevel; 5] = [
+        WeaponLevel::Single,
+        WeaponLevel::Double,
+        WeaponLevel::TripleSpread,
+        WeaponLevel::WideBeam,
+        WeaponLevel::HomingMissiles,
+    ];
+
+    pub fn definition(self) -> &'static WeaponDefinition {
+        match self {
+            WeaponLevel::Single => &SINGLE,
+            WeaponLevel::Double => &DOUBLE,
+            WeaponLevel::TripleSpread => &TRIPLE_SPREAD,
+            WeaponLevel::WideBeam => &WIDE_BEAM,
+            WeaponLevel::HomingMissiles => &HOMING_MISSILES,
+        }
+    }
+
+    /// 1-based, for the HUD.
+    pub fn number(self) -> usize {
+        Self::ALL.iter().position(|level| *level == self).unwrap_or(0) + 1
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[self.number().min(Self::ALL.len() - 1)]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[self.number().saturating_sub(2)]
+    }
+}